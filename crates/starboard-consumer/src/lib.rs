@@ -0,0 +1,382 @@
+//! Reads Starboard `Feed` accounts from native Solana programs without
+//! pulling in Anchor. The account is checked for its owner and Anchor
+//! discriminator and decoded by hand according to its layout version.
+//!
+//! ```ignore
+//! let price = starboard_consumer::get_price_no_older_than(feed_info, &Clock::get()?, 150)?;
+//! ```
+//!
+//! The protocol-wide pause lives in the Starboard config account, not in
+//! the Feed; programs that need to honour it should CPI into `read_feed`
+//! instead.
+
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+solana_program::declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+/// First 8 bytes of `sha256("account:Feed")`, prefixed to every Feed.
+pub const FEED_DISCRIMINATOR: [u8; 8] = [0x45, 0xbf, 0x10, 0xe3, 0x84, 0xbb, 0x54, 0xe3];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConsumerError {
+    WrongOwner,
+    WrongDiscriminator,
+    AccountTooSmall,
+    UnknownVersion(u8),
+    NoFinalizedValue,
+    LastRoundFailed,
+    FeedPaused,
+    ValueTooOld,
+}
+
+impl From<ConsumerError> for ProgramError {
+    fn from(e: ConsumerError) -> Self {
+        let code = match e {
+            ConsumerError::WrongOwner => 0,
+            ConsumerError::WrongDiscriminator => 1,
+            ConsumerError::AccountTooSmall => 2,
+            ConsumerError::UnknownVersion(_) => 3,
+            ConsumerError::NoFinalizedValue => 4,
+            ConsumerError::LastRoundFailed => 5,
+            ConsumerError::FeedPaused => 6,
+            ConsumerError::ValueTooOld => 7,
+        };
+        ProgramError::Custom(code)
+    }
+}
+
+/// The latest value of a feed, as `read_feed` returns it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Price {
+    pub value: i64,
+    pub confidence: u64,
+    // the price is `value * 10^exponent`
+    pub exponent: i32,
+    // slot the value was finalized at
+    pub slot: u64,
+    pub round_height: u64,
+}
+
+/// The parts of a Feed account a consumer needs, whatever its layout version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeedData {
+    pub version: u8,
+    pub description: [u8; 32],
+    pub latest_finalized_value: i64,
+    pub latest_finalized_confidence: u64,
+    pub exponent: i32,
+    pub latest_finalized_slot: u64,
+    pub latest_finalized_round_height: u64,
+    pub update_interval: u32,
+    pub height: u64,
+    pub stake_mint: Pubkey,
+    pub stale: bool,
+    pub paused: bool,
+}
+
+/// Byte offsets of a Feed layout version, counted after the discriminator.
+struct Layout {
+    description: usize,
+    latest_finalized_value: usize,
+    // None for layouts from before feeds had a confidence and exponent,
+    // whose values were unsigned whole units
+    latest_finalized_confidence: Option<usize>,
+    exponent: Option<usize>,
+    latest_finalized_slot: usize,
+    latest_finalized_round_height: usize,
+    update_interval: usize,
+    height: usize,
+    stake_mint: usize,
+    stale: usize,
+    paused: usize,
+    size: usize,
+}
+
+const LAYOUT_V1: Layout = Layout {
+    description: 1,
+    latest_finalized_value: 104,
+    latest_finalized_confidence: None,
+    exponent: None,
+    latest_finalized_slot: 112,
+    latest_finalized_round_height: 120,
+    update_interval: 128,
+    height: 304,
+    stake_mint: 368,
+    stale: 428,
+    paused: 429,
+    size: 472,
+};
+
+const LAYOUT_V2: Layout = Layout {
+    description: 1,
+    latest_finalized_value: 104,
+    latest_finalized_confidence: Some(112),
+    exponent: Some(140),
+    latest_finalized_slot: 120,
+    latest_finalized_round_height: 128,
+    update_interval: 136,
+    height: 312,
+    stake_mint: 376,
+    stale: 436,
+    paused: 437,
+    size: 480,
+};
+
+// version 3 appended the aggregation config, which consumers don't need
+const LAYOUT_V3: Layout = Layout {
+    size: 488,
+    ..LAYOUT_V2
+};
+
+// version 4 appended the reporter cap, which consumers don't need either
+const LAYOUT_V4: Layout = Layout {
+    size: 496,
+    ..LAYOUT_V2
+};
+
+fn layout(version: u8) -> Result<&'static Layout, ConsumerError> {
+    match version {
+        1 => Ok(&LAYOUT_V1),
+        2 => Ok(&LAYOUT_V2),
+        3 => Ok(&LAYOUT_V3),
+        4 => Ok(&LAYOUT_V4),
+        _ => Err(ConsumerError::UnknownVersion(version)),
+    }
+}
+
+fn read<const N: usize>(data: &[u8], offset: usize) -> [u8; N] {
+    data[offset..offset + N].try_into().unwrap()
+}
+
+impl FeedData {
+    /// Decodes raw Feed account data, discriminator included. Does not
+    /// check the owner; use `load_feed` for accounts.
+    pub fn decode(data: &[u8]) -> Result<FeedData, ConsumerError> {
+        if data.len() < 9 {
+            return Err(ConsumerError::AccountTooSmall);
+        }
+        if data[..8] != FEED_DISCRIMINATOR {
+            return Err(ConsumerError::WrongDiscriminator);
+        }
+        let data = &data[8..];
+        let version = data[0];
+        let layout = layout(version)?;
+        if data.len() < layout.size {
+            return Err(ConsumerError::AccountTooSmall);
+        }
+        let value = read(data, layout.latest_finalized_value);
+        let latest_finalized_value = match layout.exponent {
+            Some(_) => i64::from_le_bytes(value),
+            None => i64::try_from(u64::from_le_bytes(value)).unwrap_or(i64::MAX),
+        };
+        Ok(FeedData {
+            version,
+            description: read(data, layout.description),
+            latest_finalized_value,
+            latest_finalized_confidence: layout.latest_finalized_confidence
+                .map_or(0, |offset| u64::from_le_bytes(read(data, offset))),
+            exponent: layout.exponent
+                .map_or(0, |offset| i32::from_le_bytes(read(data, offset))),
+            latest_finalized_slot: u64::from_le_bytes(read(data, layout.latest_finalized_slot)),
+            latest_finalized_round_height: u64::from_le_bytes(read(data, layout.latest_finalized_round_height)),
+            update_interval: u32::from_le_bytes(read(data, layout.update_interval)),
+            height: u64::from_le_bytes(read(data, layout.height)),
+            stake_mint: Pubkey::new_from_array(read(data, layout.stake_mint)),
+            stale: data[layout.stale] != 0,
+            paused: data[layout.paused] != 0,
+        })
+    }
+
+    /// The latest value, failing unless it was finalized by the last round,
+    /// the feed is not paused and the value is at most `max_age` slots old.
+    pub fn get_price_no_older_than(&self, slot: u64, max_age: u64) -> Result<Price, ConsumerError> {
+        if self.paused {
+            return Err(ConsumerError::FeedPaused);
+        }
+        if self.latest_finalized_round_height == 0 {
+            return Err(ConsumerError::NoFinalizedValue);
+        }
+        if self.stale {
+            return Err(ConsumerError::LastRoundFailed);
+        }
+        if slot.saturating_sub(self.latest_finalized_slot) > max_age {
+            return Err(ConsumerError::ValueTooOld);
+        }
+        Ok(Price {
+            value: self.latest_finalized_value,
+            confidence: self.latest_finalized_confidence,
+            exponent: self.exponent,
+            slot: self.latest_finalized_slot,
+            round_height: self.latest_finalized_round_height,
+        })
+    }
+}
+
+/// Checks that `account` is a Feed owned by the Starboard program and decodes it.
+pub fn load_feed(account: &AccountInfo) -> Result<FeedData, ConsumerError> {
+    if *account.owner != ID {
+        return Err(ConsumerError::WrongOwner);
+    }
+    let data = account.try_borrow_data().map_err(|_| ConsumerError::AccountTooSmall)?;
+    FeedData::decode(&data)
+}
+
+/// Reads the latest price of a Feed account, failing if it is older than
+/// `max_age` slots at `clock`.
+pub fn get_price_no_older_than(account: &AccountInfo, clock: &Clock, max_age: u64) -> Result<Price, ConsumerError> {
+    load_feed(account)?.get_price_no_older_than(clock.slot, max_age)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::hash::hash;
+
+    // kept in sync with the program's Feed layout by its
+    // `consumer_fixture_matches_feed_layout` test
+    const FEED_V4: &[u8] = include_bytes!("../fixtures/feed_v4.bin");
+    // written by the program before feeds capped their reporters
+    const FEED_V3: &[u8] = include_bytes!("../fixtures/feed_v3.bin");
+    // written by the program before feeds had an aggregation mode
+    const FEED_V2: &[u8] = include_bytes!("../fixtures/feed_v2.bin");
+    // written by the program before feeds had an exponent
+    const FEED_V1: &[u8] = include_bytes!("../fixtures/feed_v1.bin");
+
+    fn v4_account(owner: Pubkey) -> AccountInfo<'static> {
+        let key = Box::leak(Box::new(Pubkey::new_unique()));
+        let owner = Box::leak(Box::new(owner));
+        let lamports = Box::leak(Box::new(0));
+        let data = Box::leak(FEED_V4.to_vec().into_boxed_slice());
+        AccountInfo::new(key, false, false, lamports, data, owner, false, 0)
+    }
+
+    #[test]
+    fn discriminator_is_anchors() {
+        assert_eq!(FEED_DISCRIMINATOR, hash(b"account:Feed").to_bytes()[..8]);
+    }
+
+    #[test]
+    fn decodes_v4_fixture() {
+        let feed = FeedData::decode(FEED_V4).unwrap();
+        assert_eq!(feed.version, 4);
+        assert_eq!(&feed.description[..13], b"SOL/USD basis");
+        assert_eq!(feed.latest_finalized_value, -4_250);
+        assert_eq!(feed.latest_finalized_confidence, 75);
+        assert_eq!(feed.exponent, -4);
+        assert_eq!(feed.latest_finalized_slot, 1_000);
+        assert_eq!(feed.latest_finalized_round_height, 41);
+        assert_eq!(feed.height, 45);
+        assert!(!feed.stale);
+        assert!(!feed.paused);
+    }
+
+    #[test]
+    fn decodes_v3_fixture() {
+        let feed = FeedData::decode(FEED_V3).unwrap();
+        assert_eq!(feed.version, 3);
+        assert_eq!(&feed.description[..13], b"SOL/USD basis");
+        assert_eq!(feed.latest_finalized_value, -4_250);
+        assert_eq!(feed.latest_finalized_confidence, 75);
+        assert_eq!(feed.exponent, -4);
+        assert_eq!(feed.latest_finalized_slot, 1_000);
+        assert_eq!(feed.latest_finalized_round_height, 41);
+        assert_eq!(feed.height, 45);
+        assert!(!feed.stale);
+        assert!(!feed.paused);
+    }
+
+    #[test]
+    fn decodes_v2_fixture() {
+        let feed = FeedData::decode(FEED_V2).unwrap();
+        assert_eq!(feed.version, 2);
+        assert_eq!(&feed.description[..13], b"SOL/USD basis");
+        assert_eq!(feed.latest_finalized_value, -4_250);
+        assert_eq!(feed.latest_finalized_confidence, 75);
+        assert_eq!(feed.exponent, -4);
+        assert_eq!(feed.latest_finalized_slot, 1_000);
+        assert_eq!(feed.latest_finalized_round_height, 41);
+        assert_eq!(feed.update_interval, 30);
+        assert_eq!(feed.height, 45);
+        assert_eq!(
+            feed.stake_mint.to_string(),
+            "So11111111111111111111111111111111111111112"
+        );
+        assert!(!feed.stale);
+        assert!(!feed.paused);
+    }
+
+    #[test]
+    fn decodes_v1_fixture() {
+        let feed = FeedData::decode(FEED_V1).unwrap();
+        assert_eq!(feed.version, 1);
+        assert_eq!(&feed.description[..7], b"SOL/USD");
+        assert_eq!(feed.latest_finalized_value, 2_150_000);
+        assert_eq!(feed.latest_finalized_confidence, 0);
+        assert_eq!(feed.exponent, 0);
+        assert_eq!(feed.latest_finalized_slot, 1_000);
+        assert_eq!(feed.latest_finalized_round_height, 41);
+        assert_eq!(feed.update_interval, 30);
+        assert_eq!(feed.height, 45);
+        assert_eq!(
+            feed.stake_mint.to_string(),
+            "So11111111111111111111111111111111111111112"
+        );
+        assert!(!feed.stale);
+        assert!(!feed.paused);
+    }
+
+    #[test]
+    fn price_respects_max_age() {
+        let feed = FeedData::decode(FEED_V4).unwrap();
+        let price = feed.get_price_no_older_than(1_150, 150).unwrap();
+        assert_eq!(price.value, -4_250);
+        assert_eq!(price.confidence, 75);
+        assert_eq!(price.exponent, -4);
+        assert_eq!(price.slot, 1_000);
+        assert_eq!(price.round_height, 41);
+        assert_eq!(feed.get_price_no_older_than(1_151, 150), Err(ConsumerError::ValueTooOld));
+    }
+
+    #[test]
+    fn stale_and_paused_feeds_have_no_price() {
+        let mut bytes = FEED_V4.to_vec();
+        bytes[8 + LAYOUT_V4.stale] = 1;
+        let feed = FeedData::decode(&bytes).unwrap();
+        assert_eq!(feed.get_price_no_older_than(1_000, 150), Err(ConsumerError::LastRoundFailed));
+
+        let mut bytes = FEED_V4.to_vec();
+        bytes[8 + LAYOUT_V4.paused] = 1;
+        let feed = FeedData::decode(&bytes).unwrap();
+        assert_eq!(feed.get_price_no_older_than(1_000, 150), Err(ConsumerError::FeedPaused));
+    }
+
+    #[test]
+    fn rejects_bad_accounts() {
+        let mut bytes = FEED_V4.to_vec();
+        bytes[0] ^= 1;
+        assert_eq!(FeedData::decode(&bytes), Err(ConsumerError::WrongDiscriminator));
+
+        let mut bytes = FEED_V4.to_vec();
+        bytes[8] = 9;
+        assert_eq!(FeedData::decode(&bytes), Err(ConsumerError::UnknownVersion(9)));
+
+        assert_eq!(FeedData::decode(&FEED_V4[..100]), Err(ConsumerError::AccountTooSmall));
+        // a v4 header on an account cut to the v3 size
+        assert_eq!(FeedData::decode(&FEED_V4[..8 + 488]), Err(ConsumerError::AccountTooSmall));
+    }
+
+    #[test]
+    fn checks_owner() {
+        let clock = Clock { slot: 1_000, ..Clock::default() };
+        let account = v4_account(ID);
+        assert_eq!(get_price_no_older_than(&account, &clock, 150).unwrap().value, -4_250);
+
+        let account = v4_account(Pubkey::new_unique());
+        assert_eq!(get_price_no_older_than(&account, &clock, 150), Err(ConsumerError::WrongOwner));
+    }
+}
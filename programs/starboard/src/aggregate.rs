@@ -0,0 +1,395 @@
+use crate::*;
+
+/// How `finalize` turns a round's certified reports into the feed's
+/// value, stored as `Feed.aggregation` and snapshotted into each Round.
+///
+/// All strategies use integer arithmetic only, with i128 intermediates
+/// so sums and products of i64 values and u64 weights cannot overflow.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum AggregationKind {
+    Median,
+    // mean of the reports left after dropping `trim_bps` of them from
+    // each end
+    TrimmedMean,
+    // median where each report counts with its reporter's stake
+    StakeWeightedMedian,
+    // mean where each report counts with the inverse of its confidence
+    ConfidenceWeightedMean,
+}
+
+impl TryFrom<u8> for AggregationKind {
+    type Error = anchor_lang::error::Error;
+
+    fn try_from(kind: u8) -> Result<Self> {
+        use AggregationKind::*;
+        Ok(match kind {
+            0 => Median,
+            1 => TrimmedMean,
+            2 => StakeWeightedMedian,
+            3 => ConfidenceWeightedMean,
+            _ => return err!(StarboardError::InvalidAggregation),
+        })
+    }
+}
+
+/// One certified report as seen by the aggregator.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Sample {
+    pub value: i64,
+    pub weight: u64,
+    pub confidence: u64,
+}
+
+// fixed-point scale of the inverse confidence widths, small enough that
+// a weight times any i64 value stays far inside i128
+const INVERSE_SCALE: u128 = 1_000_000_000_000;
+
+// narrowest confidence a report is weighted with, relative to its value,
+// so a report cannot buy unbounded weight by claiming zero width
+const MIN_CONFIDENCE_BPS: u128 = 10;
+
+/// Middle value of `values`, averaging the two middle values of an even
+/// count. Sorts `values` in place.
+pub fn median<T>(values: &mut [T]) -> T
+where
+    T: Copy + Ord + Into<i128> + TryFrom<i128>,
+{
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    if values.len() % 2 == 1 {
+        values[mid]
+    } else {
+        let (a, b) = (values[mid - 1], values[mid]);
+        // the midpoint lies between a and b, so it always fits in T
+        T::try_from((a.into() + b.into()) / 2).unwrap_or(a)
+    }
+}
+
+/// Mean of `values` after dropping `trim_bps` of the count from each end,
+/// rounded toward zero. At least one value is always kept. Sorts
+/// `values` in place.
+pub fn trimmed_mean(values: &mut [i64], trim_bps: u32) -> i64 {
+    values.sort_unstable();
+    let len = values.len();
+    let trim = (len as u128 * trim_bps as u128 / BPS_DENOMINATOR) as usize;
+    let trim = trim.min((len - 1) / 2);
+    let kept = &values[trim..len - trim];
+    let sum: i128 = kept.iter().map(|v| *v as i128).sum();
+    // the mean lies between the smallest and largest kept value
+    (sum / kept.len() as i128) as i64
+}
+
+/// Value at which half the total weight lies on either side. When the
+/// cumulative weight hits exactly half between two values, their
+/// midpoint is taken, as for an even count in `median`. Falls back to
+/// the plain median if no sample carries weight. Sorts `samples` in place.
+pub fn weighted_median(samples: &mut [Sample]) -> i64 {
+    samples.sort_unstable_by_key(|s| s.value);
+    let total: u128 = samples.iter().map(|s| s.weight as u128).sum();
+    if total == 0 {
+        let mut values: Vec<i64> = samples.iter().map(|s| s.value).collect();
+        return median(&mut values);
+    }
+    let mut cumulative = 0u128;
+    for (i, sample) in samples.iter().enumerate() {
+        cumulative += sample.weight as u128;
+        if cumulative * 2 == total {
+            if let Some(next) = samples[i + 1..].iter().find(|s| s.weight > 0) {
+                return ((sample.value as i128 + next.value as i128) / 2) as i64;
+            }
+        }
+        if cumulative * 2 >= total {
+            return sample.value;
+        }
+    }
+    unreachable!()
+}
+
+/// Mean of the samples weighted by `INVERSE_SCALE / confidence`, so a
+/// report half as wide counts twice as much. Confidences narrower than
+/// `MIN_CONFIDENCE_BPS` of the value (and at least one unit) are widened
+/// to it, and the heaviest report is cut down to the weight of all the
+/// others together, so no single reporter can set the value alone.
+/// Rounded toward zero, and falls back to the plain median if every
+/// confidence is too wide to carry weight.
+pub fn confidence_weighted_mean(samples: &[Sample]) -> i64 {
+    let mut weights: Vec<u128> = samples
+        .iter()
+        .map(|s| {
+            let floor = s.value.unsigned_abs() as u128 * MIN_CONFIDENCE_BPS / BPS_DENOMINATOR;
+            INVERSE_SCALE / (s.confidence as u128).max(floor).max(1)
+        })
+        .collect();
+    let mut total: u128 = weights.iter().sum();
+    if let Some(heaviest) = weights.iter_mut().max() {
+        let others = total - *heaviest;
+        if *heaviest > others {
+            total = others * 2;
+            *heaviest = others;
+        }
+    }
+    if total == 0 {
+        let mut values: Vec<i64> = samples.iter().map(|s| s.value).collect();
+        return median(&mut values);
+    }
+    let weighted: i128 = samples
+        .iter()
+        .zip(&weights)
+        .map(|(s, weight)| s.value as i128 * *weight as i128)
+        .sum();
+    // the mean lies between the smallest and largest value
+    (weighted / total as i128) as i64
+}
+
+/// Square root of `n`, rounded up.
+pub fn sqrt_ceil(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    // Newton's method from above converges on the floor of the root
+    let mut x = 1u128 << (64 - n.leading_zeros() / 2);
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            break;
+        }
+        x = y;
+    }
+    if x * x < n { x + 1 } else { x }
+}
+
+/// Confidence of `aggregate` over a non-empty set of samples: the median
+/// reported confidence and the median absolute deviation of the reports
+/// from the aggregate, added in quadrature and rounded up. It widens both
+/// when reporters are unsure and when they disagree.
+pub fn output_confidence(samples: &[Sample], aggregate: i64) -> u64 {
+    let mut confidences: Vec<u64> = samples.iter().map(|s| s.confidence).collect();
+    let mut deviations: Vec<u64> = samples.iter().map(|s| s.value.abs_diff(aggregate)).collect();
+    let reported = median(&mut confidences) as u128;
+    let dispersion = median(&mut deviations) as u128;
+    // each square fits in u128, a saturated sum still roots to over u64::MAX
+    let confidence = sqrt_ceil((reported * reported).saturating_add(dispersion * dispersion));
+    confidence.min(u64::MAX as u128) as u64
+}
+
+/// The aggregate of a non-empty set of samples under `kind`.
+pub fn aggregate(kind: AggregationKind, samples: &mut [Sample], trim_bps: u32) -> i64 {
+    match kind {
+        AggregationKind::Median => {
+            let mut values: Vec<i64> = samples.iter().map(|s| s.value).collect();
+            median(&mut values)
+        }
+        AggregationKind::TrimmedMean => {
+            let mut values: Vec<i64> = samples.iter().map(|s| s.value).collect();
+            trimmed_mean(&mut values, trim_bps)
+        }
+        AggregationKind::StakeWeightedMedian => weighted_median(samples),
+        AggregationKind::ConfidenceWeightedMean => confidence_weighted_mean(samples),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use AggregationKind::*;
+
+    fn samples(values_and_weights: &[(i64, u64)]) -> Vec<Sample> {
+        values_and_weights
+            .iter()
+            .map(|(value, weight)| Sample { value: *value, weight: *weight, confidence: 0 })
+            .collect()
+    }
+
+    fn with_confidence(values_and_confidences: &[(i64, u64)]) -> Vec<Sample> {
+        values_and_confidences
+            .iter()
+            .map(|(value, confidence)| Sample { value: *value, weight: 0, confidence: *confidence })
+            .collect()
+    }
+
+    #[test]
+    fn median_of_odd_and_even_counts() {
+        assert_eq!(median(&mut [3i64, 1, 2]), 2);
+        assert_eq!(median(&mut [4i64, 1, 3, 2]), 2);
+        assert_eq!(median(&mut [5i64]), 5);
+        assert_eq!(median(&mut [u64::MAX, u64::MAX - 2]), u64::MAX - 1);
+    }
+
+    #[test]
+    fn median_handles_negative_values() {
+        assert_eq!(median(&mut [-3i64, -1, -2]), -2);
+        assert_eq!(median(&mut [-1i64, -2]), -1);
+        assert_eq!(median(&mut [i64::MIN, i64::MAX]), 0);
+    }
+
+    #[test]
+    fn trimmed_mean_drops_both_ends() {
+        // 20% of 10 reports is 2 from each end
+        let mut values = [1000i64, 1, 2, 3, 4, 5, 6, 7, 8, -1000];
+        assert_eq!(trimmed_mean(&mut values, 2000), (2 + 3 + 4 + 5 + 6 + 7) / 6);
+    }
+
+    #[test]
+    fn trimmed_mean_without_trim_is_the_mean() {
+        assert_eq!(trimmed_mean(&mut [1i64, 2, 3, 6], 0), 3);
+        assert_eq!(trimmed_mean(&mut [-1i64, -2], 0), -1);
+    }
+
+    #[test]
+    fn trimmed_mean_rounds_the_trim_down() {
+        // 10% of 5 reports rounds down to nothing trimmed
+        assert_eq!(trimmed_mean(&mut [0i64, 0, 0, 0, 50], 1000), 10);
+    }
+
+    #[test]
+    fn trimmed_mean_always_keeps_a_value() {
+        assert_eq!(trimmed_mean(&mut [1i64, 2, 100], 4999), 2);
+        assert_eq!(trimmed_mean(&mut [1i64, 2, 4, 100], 10_000), 3);
+        assert_eq!(trimmed_mean(&mut [7i64], 10_000), 7);
+    }
+
+    #[test]
+    fn trimmed_mean_does_not_overflow() {
+        assert_eq!(trimmed_mean(&mut [i64::MAX, i64::MAX], 0), i64::MAX);
+        assert_eq!(trimmed_mean(&mut [i64::MIN, i64::MIN, i64::MIN], 0), i64::MIN);
+    }
+
+    #[test]
+    fn weighted_median_follows_stake() {
+        let mut s = samples(&[(10, 1), (20, 1), (30, 5)]);
+        assert_eq!(weighted_median(&mut s), 30);
+        let mut s = samples(&[(10, 5), (20, 1), (30, 1)]);
+        assert_eq!(weighted_median(&mut s), 10);
+    }
+
+    #[test]
+    fn weighted_median_with_equal_weights_is_the_median() {
+        let mut s = samples(&[(3, 7), (1, 7), (2, 7)]);
+        assert_eq!(weighted_median(&mut s), 2);
+        let mut s = samples(&[(4, 7), (1, 7), (3, 7), (2, 7)]);
+        assert_eq!(weighted_median(&mut s), 2);
+    }
+
+    #[test]
+    fn weighted_median_splits_exact_halves() {
+        let mut s = samples(&[(-10, 3), (20, 3)]);
+        assert_eq!(weighted_median(&mut s), 5);
+        // a weightless sample in between doesn't count as the next value
+        let mut s = samples(&[(10, 3), (12, 0), (20, 3)]);
+        assert_eq!(weighted_median(&mut s), 15);
+    }
+
+    #[test]
+    fn weighted_median_without_weight_falls_back_to_median() {
+        let mut s = samples(&[(1, 0), (9, 0), (5, 0)]);
+        assert_eq!(weighted_median(&mut s), 5);
+    }
+
+    #[test]
+    fn weighted_median_does_not_overflow() {
+        let mut s = samples(&[(i64::MAX, u64::MAX), (i64::MIN, u64::MAX), (0, u64::MAX)]);
+        assert_eq!(weighted_median(&mut s), 0);
+    }
+
+    #[test]
+    fn confidence_weighted_mean_favours_narrow_reports() {
+        // the report at 100 is twice as confident as the other two
+        let s = with_confidence(&[(100, 2), (200, 4), (300, 4)]);
+        assert_eq!(confidence_weighted_mean(&s), 175);
+        let s = with_confidence(&[(-100, 4), (-200, 4), (-300, 2)]);
+        assert_eq!(confidence_weighted_mean(&s), -225);
+    }
+
+    #[test]
+    fn confidence_weighted_mean_with_equal_widths_is_the_mean() {
+        let s = with_confidence(&[(1, 5), (2, 5), (6, 5)]);
+        assert_eq!(confidence_weighted_mean(&s), 3);
+    }
+
+    #[test]
+    fn confidence_weighted_mean_treats_zero_width_as_one() {
+        let s = with_confidence(&[(10, 0), (20, 1)]);
+        assert_eq!(confidence_weighted_mean(&s), 15);
+    }
+
+    #[test]
+    fn confidence_weighted_mean_widens_confidence_to_the_floor() {
+        // 0.1% of 10_000 is 10, so the first report counts twice as much
+        // as each of the others rather than a hundred times
+        let s = with_confidence(&[(10_000, 0), (20_000, 20), (20_000, 20), (20_000, 20)]);
+        assert_eq!(confidence_weighted_mean(&s), 16_000);
+    }
+
+    #[test]
+    fn confidence_weighted_mean_caps_a_tight_outlier() {
+        // the outlier is ten times as confident as each honest report, but
+        // carries no more weight than the four of them together
+        let s = with_confidence(&[(100, 10), (100, 10), (100, 10), (100, 10), (1_000, 0)]);
+        assert_eq!(confidence_weighted_mean(&s), 550);
+        // and a lone report still sets the value
+        let s = with_confidence(&[(1_000, 0)]);
+        assert_eq!(confidence_weighted_mean(&s), 1_000);
+    }
+
+    #[test]
+    fn confidence_weighted_mean_without_weight_falls_back_to_median() {
+        let s = with_confidence(&[(1, u64::MAX), (9, u64::MAX), (5, u64::MAX)]);
+        assert_eq!(confidence_weighted_mean(&s), 5);
+    }
+
+    #[test]
+    fn confidence_weighted_mean_does_not_overflow() {
+        let s = with_confidence(&[(i64::MAX, 0), (i64::MAX, 0), (i64::MAX, 0)]);
+        assert_eq!(confidence_weighted_mean(&s), i64::MAX);
+        let s = with_confidence(&[(i64::MIN, 0), (i64::MAX, 0)]);
+        assert_eq!(confidence_weighted_mean(&s), 0);
+    }
+
+    #[test]
+    fn sqrt_rounds_up() {
+        assert_eq!(sqrt_ceil(0), 0);
+        assert_eq!(sqrt_ceil(1), 1);
+        assert_eq!(sqrt_ceil(2), 2);
+        assert_eq!(sqrt_ceil(16), 4);
+        assert_eq!(sqrt_ceil(17), 5);
+        assert_eq!(sqrt_ceil(u64::MAX as u128 * u64::MAX as u128), u64::MAX as u128);
+        assert_eq!(sqrt_ceil(u128::MAX), 1 << 64);
+    }
+
+    #[test]
+    fn output_confidence_adds_reports_and_dispersion() {
+        // agreeing reporters: only their own confidence counts
+        let s = with_confidence(&[(100, 3), (100, 3), (100, 3)]);
+        assert_eq!(output_confidence(&s, 100), 3);
+        // certain reporters that disagree: only the dispersion counts
+        let s = with_confidence(&[(96, 0), (100, 0), (104, 0)]);
+        assert_eq!(output_confidence(&s, 100), 4);
+        // both, in quadrature: sqrt(3^2 + 4^2)
+        let s = with_confidence(&[(96, 3), (100, 3), (104, 3)]);
+        assert_eq!(output_confidence(&s, 100), 5);
+    }
+
+    #[test]
+    fn output_confidence_saturates() {
+        let s = with_confidence(&[(i64::MIN, u64::MAX), (i64::MIN, u64::MAX)]);
+        assert_eq!(output_confidence(&s, i64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn aggregate_dispatches_on_kind() {
+        let s = samples(&[(1, 1), (2, 1), (3, 1), (100, 10)]);
+        assert_eq!(aggregate(Median, &mut s.clone(), 0), 2);
+        assert_eq!(aggregate(TrimmedMean, &mut s.clone(), 2500), 2);
+        assert_eq!(aggregate(StakeWeightedMedian, &mut s.clone(), 0), 100);
+        assert_eq!(aggregate(ConfidenceWeightedMean, &mut s.clone(), 0), 26);
+    }
+
+    #[test]
+    fn u8_round_trip() {
+        for kind in [Median, TrimmedMean, StakeWeightedMedian, ConfidenceWeightedMean] {
+            assert_eq!(AggregationKind::try_from(kind as u8).unwrap(), kind);
+        }
+        assert!(AggregationKind::try_from(4).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use crate::*;
+
+/// Settings `init_feed` creates a feed with. Slash parameters left as
+/// None take the protocol defaults from `Starboard`, and the stake mint
+/// must be `Starboard::default_stake_mint` unless `custom_stake_mint` is set.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct FeedParams {
+    pub description: [u8; 32],
+    pub exponent: i32,
+    pub update_interval: u32,
+    pub cert_bond: u64,
+    pub slash_band_bps: Option<u32>,
+    pub slash_mad_multiple: Option<u32>,
+    pub reward_per_round: u64,
+    pub min_reporters: u32,
+    pub max_reporters: u32,
+    pub crank_bounty: u64,
+    pub min_stake: u64,
+    pub max_stake: u64,
+    pub native_stake: bool,
+    pub custom_stake_mint: bool,
+    pub aggregation: u8,
+    pub trim_bps: u32,
+}
+
+/// Changes `set_feed_config` makes to a feed. Settings left as None
+/// are kept.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct FeedConfigUpdate {
+    pub description: Option<[u8; 32]>,
+    pub update_interval: Option<u32>,
+    pub min_stake: Option<u64>,
+    pub max_stake: Option<u64>,
+    pub min_reporters: Option<u32>,
+    pub max_reporters: Option<u32>,
+    pub slash_band_bps: Option<u32>,
+    pub slash_mad_multiple: Option<u32>,
+    pub aggregation: Option<u8>,
+    pub trim_bps: Option<u32>,
+}
+
+impl Feed {
+    pub fn config_is_valid(&self) -> bool {
+        self.update_interval > 0 && self.min_stake > 0 && self.min_reporters > 0 &&
+            self.max_reporters >= self.min_reporters && self.max_reporters <= MAX_REPORTERS &&
+            (self.max_stake == 0 || self.max_stake >= self.min_stake) &&
+            AggregationKind::try_from(self.aggregation).is_ok() &&
+            (self.trim_bps as u128) < BPS_DENOMINATOR / 2 &&
+            self.checked_round_cost().is_some()
+    }
+
+    pub fn apply_config(&mut self, update: &FeedConfigUpdate) {
+        if let Some(description) = update.description {
+            self.description = description;
+        }
+        if let Some(update_interval) = update.update_interval {
+            self.update_interval = update_interval;
+        }
+        if let Some(min_stake) = update.min_stake {
+            self.min_stake = min_stake;
+        }
+        if let Some(max_stake) = update.max_stake {
+            self.max_stake = max_stake;
+        }
+        if let Some(min_reporters) = update.min_reporters {
+            self.min_reporters = min_reporters;
+        }
+        if let Some(max_reporters) = update.max_reporters {
+            self.max_reporters = max_reporters;
+        }
+        if let Some(slash_band_bps) = update.slash_band_bps {
+            self.slash_band_bps = slash_band_bps;
+        }
+        if let Some(slash_mad_multiple) = update.slash_mad_multiple {
+            self.slash_mad_multiple = slash_mad_multiple;
+        }
+        if let Some(aggregation) = update.aggregation {
+            self.aggregation = aggregation;
+        }
+        if let Some(trim_bps) = update.trim_bps {
+            self.trim_bps = trim_bps;
+        }
+    }
+}
@@ -14,7 +14,72 @@ use anchor_spl::{
 };
 
 #[derive(Accounts)]
-#[instruction(id: [u8; 32], description: [u8; 32], update_interval: u32)]
+pub struct InitStarboard<'info> {
+    #[account(
+        init,
+        seeds = [STARBOARD_SEED],
+        bump,
+        payer = admin,
+        space = 8 + std::mem::size_of::<Starboard>(),
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    // only the program's upgrade authority may create the singleton, so
+    // it cannot be taken over by whoever calls first after a deploy
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Starboard>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ StarboardError::NotUpgradeAuthority,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateStarboard<'info> {
+    #[account(
+        mut,
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        has_one = admin,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolPause<'info> {
+    #[account(
+        mut,
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        has_one = admin,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeedPause<'info> {
+    #[account(mut)]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        constraint = signer.key() == feed.load()?.authority ||
+            signer.key() == starboard.load()?.admin
+            @ StarboardError::NotFeedAuthorityOrAdmin,
+    )]
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: [u8; 32])]
 pub struct InitFeed<'info> {
     #[account(
         init,
@@ -24,13 +89,48 @@ pub struct InitFeed<'info> {
         ],
         bump,
         payer = payer,
-        space = (32*2) +(33*5)+ (8*2) + 4 + 8 + 1 + 8 + 8 + 8
+        space = 8 + std::mem::size_of::<Feed>()
     )]
     pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        init,
+        seeds = [
+            FEED_VAULT_SEED,
+            feed.key().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        token::mint = stake_mint,
+        token::authority = program_as_signer,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        seeds = [
+            FEED_LEASE_SEED,
+            feed.key().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        token::mint = stake_mint,
+        token::authority = program_as_signer,
+    )]
+    pub lease: Account<'info, TokenAccount>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    pub stake_mint: Account<'info, Mint>,
+    /// CHECK: program as signer
+    #[account(seeds=[b"program",b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
@@ -45,7 +145,7 @@ pub struct InitRound<'info> {
         ],
         bump,
         payer = payer,
-        space = 80+32,
+        space = 8 + std::mem::size_of::<Round>(),
     )]
     pub round: AccountLoader<'info, Round>,
     #[account(
@@ -53,6 +153,13 @@ pub struct InitRound<'info> {
         constraint = !feed.load()?.started,
     )]
     pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -63,61 +170,253 @@ pub struct InitRound<'info> {
 pub struct StartFeed<'info> {
     #[account(mut)]
     pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeedConfig<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TransferFeedAuthority<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FundLease<'info> {
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        mut,
+        address = feed.load()?.lease,
+    )]
+    pub lease: Account<'info, TokenAccount>,
+    pub funder: Signer<'info>,
+    #[account(
+        mut,
+        token::mint = stake_mint,
+        token::authority = funder,
+    )]
+    pub funder_tokens: Account<'info, TokenAccount>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawLease<'info> {
+    #[account(has_one = lease_authority)]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        mut,
+        address = feed.load()?.lease,
+    )]
+    pub lease: Account<'info, TokenAccount>,
+    pub lease_authority: Signer<'info>,
+    #[account(
+        mut,
+        token::mint = stake_mint,
+    )]
+    pub recipient_tokens: Account<'info, TokenAccount>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
+    /// CHECK: program as signer
+    #[account(seeds=[b"program",b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
 pub struct StartStaking<'info> {
     #[account(mut)]
     pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
     #[account(
         mut,
         has_one = feed
     )]
     pub round: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        token::mint = stake_mint,
+    )]
+    pub keeper_tokens: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = feed.load()?.lease,
+    )]
+    pub lease: Account<'info, TokenAccount>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
+    /// CHECK: program as signer
+    #[account(seeds=[b"program",b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
 pub struct StartReporting<'info> {
     #[account(mut)]
     pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
     #[account(
         mut,
         has_one = feed
     )]
     pub round: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        token::mint = stake_mint,
+    )]
+    pub keeper_tokens: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = feed.load()?.lease,
+    )]
+    pub lease: Account<'info, TokenAccount>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
+    /// CHECK: program as signer
+    #[account(seeds=[b"program",b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
 pub struct StartComitting<'info> {
     #[account(mut)]
     pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
     #[account(
         mut,
         has_one = feed
     )]
     pub round: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        token::mint = stake_mint,
+    )]
+    pub keeper_tokens: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = feed.load()?.lease,
+    )]
+    pub lease: Account<'info, TokenAccount>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
+    /// CHECK: program as signer
+    #[account(seeds=[b"program",b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
 pub struct StartCertifying<'info> {
     #[account(mut)]
     pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
     #[account(
         mut,
         has_one = feed
     )]
     pub round: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        token::mint = stake_mint,
+    )]
+    pub keeper_tokens: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = feed.load()?.lease,
+    )]
+    pub lease: Account<'info, TokenAccount>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
+    /// CHECK: program as signer
+    #[account(seeds=[b"program",b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
 pub struct StartFinalizing<'info> {
     #[account(mut)]
     pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
     #[account(
         mut,
         has_one = feed
     )]
     pub round: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        token::mint = stake_mint,
+    )]
+    pub keeper_tokens: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = feed.load()?.lease,
+    )]
+    pub lease: Account<'info, TokenAccount>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
+    /// CHECK: program as signer
+    #[account(seeds=[b"program",b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
@@ -130,7 +429,7 @@ pub struct Stake<'info> {
             round.key().as_ref(),
         ],
         bump,
-        space = 96+32,
+        space = 8 + std::mem::size_of::<Escrow>(),
         payer = voter,
     )]
     pub escrow: AccountLoader<'info, Escrow>,
@@ -142,7 +441,7 @@ pub struct Stake<'info> {
         ],
         bump,
         payer = voter,
-        token::mint = native_mint,
+        token::mint = stake_mint,
         token::authority = program_as_signer,
     )]
     pub escrow_token: Account<'info, TokenAccount>,
@@ -150,12 +449,22 @@ pub struct Stake<'info> {
     pub voter: Signer<'info>,
     #[account(
         mut,
-        token::mint = native_mint,
+        token::mint = stake_mint,
         token::authority = voter,
     )]
     pub voter_token_account: Account<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = !feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
     pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
     #[account(
         mut,
         has_one = feed
@@ -163,26 +472,93 @@ pub struct Stake<'info> {
     pub round: AccountLoader<'info, Round>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
-    #[account(address=NATIVE_MINT)]
-    pub native_mint: Account<'info, Mint>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
     pub token_program: Program<'info, Token>,
     /// CHECK: program as signer
     #[account(seeds=[b"program",b"signer"], bump)]
     pub program_as_signer: UncheckedAccount<'info>,
 }
 
+#[derive(Accounts)]
+pub struct TopUpStake<'info> {
+    #[account(
+        mut,
+        seeds = [
+            ESCROW_SEED,
+            voter.key().as_ref(),
+            round.key().as_ref(),
+        ],
+        bump = escrow.load()?.bump,
+        constraint = escrow.load()?.staker == voter.key(),
+        has_one = feed,
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+    #[account(
+        mut,
+        address = escrow.load()?.token_account,
+    )]
+    pub escrow_token: Account<'info, TokenAccount>,
+    pub voter: Signer<'info>,
+    #[account(
+        mut,
+        token::mint = stake_mint,
+        token::authority = voter,
+    )]
+    pub voter_token_account: Account<'info, TokenAccount>,
+    #[account(
+        constraint = !feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        has_one = feed
+    )]
+    pub round: AccountLoader<'info, Round>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
 #[derive(Accounts)]
 pub struct Report<'info> {
+    #[account(
+        constraint = !feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
     pub feed: AccountLoader<'info, Feed>,
     #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        mut,
         has_one=feed,
     )]
     pub round: AccountLoader<'info, Round>,
     #[account(mut)]
     pub reporter: Signer<'info>,
+    #[account(
+        seeds = [
+            ESCROW_SEED,
+            reporter.key().as_ref(),
+            round.key().as_ref(),
+        ],
+        bump = escrow.load()?.bump,
+        has_one = feed,
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
     #[account(
         mut,
-        token::mint = native_mint,
+        token::mint = stake_mint,
         token::authority = reporter,
     )]
     pub reporter_token: Account<'info, TokenAccount>,
@@ -195,7 +571,7 @@ pub struct Report<'info> {
         ],
         bump,
         payer=reporter,
-        space=8+8+32+32+32+32+8+1+8,
+        space = 8 + std::mem::size_of::<ReportRecord>(),
     )]
     pub report_record: AccountLoader<'info, ReportRecord>,
     #[account(
@@ -206,12 +582,12 @@ pub struct Report<'info> {
         ],
         bump,
         payer = reporter,
-        token::mint = native_mint,
+        token::mint = stake_mint,
         token::authority = program_as_signer,
     )]
     pub report_escrow: Account<'info, TokenAccount>,
-    #[account(address=NATIVE_MINT)]
-    pub native_mint: Account<'info, Mint>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
     /// CHECK: program as signer
     #[account(seeds=[b"program",b"signer"], bump)]
     pub program_as_signer: UncheckedAccount<'info>,
@@ -223,34 +599,101 @@ pub struct Report<'info> {
 #[derive(Accounts)]
 pub struct Comitt<'info> {
     pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
     #[account(
         has_one=feed,
     )]
     pub round: AccountLoader<'info, Round>,
     #[account(
         mut,
+        seeds = [
+            ESCROW_SEED,
+            staker.key().as_ref(),
+            round.key().as_ref(),
+        ],
+        bump = escrow.load()?.bump,
+        has_one=staker,
+        has_one=feed,
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+    pub staker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Reveal<'info> {
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        has_one=feed,
+    )]
+    pub round: AccountLoader<'info, Round>,
+    #[account(
+        seeds = [
+            ESCROW_SEED,
+            staker.key().as_ref(),
+            round.key().as_ref(),
+        ],
+        bump = escrow.load()?.bump,
         has_one=staker,
+        has_one=feed,
     )]
     pub escrow: AccountLoader<'info, Escrow>,
+    #[account(
+        mut,
+        seeds=[
+            REPORT_RECORD_SEED,
+            round.key().as_ref(),
+            staker.key().as_ref(),
+        ],
+        bump,
+        has_one=escrow,
+        has_one=round,
+    )]
+    pub report_record: AccountLoader<'info, ReportRecord>,
     pub staker: Signer<'info>,
 }
 
 #[derive(Accounts)]
 pub struct Certify<'info> {
+    #[account(
+        constraint = !feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
     pub feed: AccountLoader<'info, Feed>,
     #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        mut,
         has_one=feed,
     )]
     pub round: AccountLoader<'info, Round>,
     #[account(
+        mut,
         has_one=round,
+        has_one=feed,
     )]
     pub report_record: AccountLoader<'info, ReportRecord>,
     #[account(mut)]
     pub certifier: Signer<'info>,
     #[account(
         mut,
-        token::mint = native_mint,
+        token::mint = stake_mint,
         token::authority = certifier
     )]
     pub certifier_tokens: Account<'info, TokenAccount>,
@@ -262,7 +705,7 @@ pub struct Certify<'info> {
         ],
         bump,
         payer = certifier,
-        token::mint = native_mint,
+        token::mint = stake_mint,
         token::authority = program_as_signer,
     )]
     pub certifier_escrow: Account<'info, TokenAccount>,
@@ -275,7 +718,7 @@ pub struct Certify<'info> {
         ],
         bump,
         payer=certifier,
-        space=8+8+32+32+32+32+8+1+8,
+        space = 8 + std::mem::size_of::<CertRecord>(),
     )]
     pub cert_record: AccountLoader<'info, CertRecord>,
     /// CHECK: program as signer
@@ -284,8 +727,803 @@ pub struct Certify<'info> {
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
     pub token_program: Program<'info, Token>,
-    pub native_mint: Account<'info, Mint>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
 }
 
 #[derive(Accounts)]
-pub struct Finalize {}
+pub struct Finalize<'info> {
+    #[account(mut)]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        mut,
+        has_one = feed
+    )]
+    pub round: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        address = feed.load()?.vault,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = feed.load()?.lease,
+    )]
+    pub lease: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury.owner == starboard.load()?.treasury &&
+            treasury.mint == feed.load()?.stake_mint
+            @ StarboardError::InvalidTreasury,
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+    /// CHECK: program as signer
+    #[account(seeds=[b"program",b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ReadFeed<'info> {
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+}
+
+#[derive(Accounts)]
+pub struct FailRound<'info> {
+    #[account(mut)]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        mut,
+        has_one = feed
+    )]
+    pub round: AccountLoader<'info, Round>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawStake<'info> {
+    #[account(
+        constraint = !feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        has_one = feed
+    )]
+    pub round: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        seeds = [
+            ESCROW_SEED,
+            staker.key().as_ref(),
+            round.key().as_ref(),
+        ],
+        bump = escrow.load()?.bump,
+        has_one = staker,
+        has_one = feed,
+        close = staker,
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+    #[account(
+        mut,
+        seeds = [
+            ESCROW_TOKEN_SEED,
+            escrow.key().as_ref()
+        ],
+        bump,
+    )]
+    pub escrow_token: Account<'info, TokenAccount>,
+    /// CHECK: the staker's ReportRecord for this round, possibly uninitialized
+    #[account(
+        seeds=[
+            REPORT_RECORD_SEED,
+            round.key().as_ref(),
+            staker.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub report_record: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    #[account(
+        mut,
+        token::mint = stake_mint,
+        token::authority = staker,
+    )]
+    pub staker_tokens: Account<'info, TokenAccount>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
+    /// CHECK: program as signer
+    #[account(seeds=[b"program",b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(
+        mut,
+        constraint = !feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        mut,
+        has_one=feed,
+        has_one=reporter,
+    )]
+    pub report_record: AccountLoader<'info, ReportRecord>,
+    pub reporter: Signer<'info>,
+    #[account(
+        mut,
+        token::mint = stake_mint,
+        token::authority = reporter,
+    )]
+    pub reporter_tokens: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = feed.load()?.vault,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
+    /// CHECK: program as signer
+    #[account(seeds=[b"program",b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimCertRewards<'info> {
+    #[account(
+        mut,
+        constraint = !feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        has_one=feed,
+    )]
+    pub round: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        has_one=feed,
+        has_one=round,
+    )]
+    pub report_record: AccountLoader<'info, ReportRecord>,
+    #[account(
+        mut,
+        has_one=report_record,
+        has_one=certifier,
+        close=certifier,
+    )]
+    pub cert_record: AccountLoader<'info, CertRecord>,
+    #[account(
+        mut,
+        address = cert_record.load()?.escrow,
+    )]
+    pub certifier_escrow: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub certifier: Signer<'info>,
+    #[account(
+        mut,
+        token::mint = stake_mint,
+        token::authority = certifier,
+    )]
+    pub certifier_tokens: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = feed.load()?.vault,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
+    /// CHECK: program as signer
+    #[account(seeds=[b"program",b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseReport<'info> {
+    #[account(
+        constraint = !feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        has_one = feed
+    )]
+    pub round: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        has_one=feed,
+        has_one=round,
+        has_one=reporter,
+        close=reporter,
+    )]
+    pub report_record: AccountLoader<'info, ReportRecord>,
+    #[account(
+        mut,
+        seeds=[
+            ESCROW_TOKEN_SEED,
+            report_record.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub report_escrow: Account<'info, TokenAccount>,
+    /// CHECK: the reporter's Escrow, which must be closed if it was slashed
+    #[account(address = report_record.load()?.escrow)]
+    pub escrow: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+    #[account(
+        mut,
+        token::mint = stake_mint,
+        token::authority = reporter,
+    )]
+    pub reporter_tokens: Account<'info, TokenAccount>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
+    /// CHECK: program as signer
+    #[account(seeds=[b"program",b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SlashReporter<'info> {
+    #[account(
+        constraint = !feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        has_one=feed,
+        has_one=escrow,
+        constraint = report_record.load()?.slashed @ StarboardError::NotSlashed,
+    )]
+    pub report_record: AccountLoader<'info, ReportRecord>,
+    #[account(
+        mut,
+        has_one=staker,
+        close=staker,
+        constraint = escrow.load()?.round_height == report_record.load()?.round_height
+            @ StarboardError::EscrowRoundMismatch,
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+    #[account(
+        mut,
+        seeds=[
+            ESCROW_TOKEN_SEED,
+            escrow.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub escrow_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = feed.load()?.vault,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    /// CHECK: receives the rent of the closed escrow accounts
+    #[account(mut)]
+    pub staker: UncheckedAccount<'info>,
+    /// CHECK: program as signer
+    #[account(seeds=[b"program",b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SlashCertifier<'info> {
+    #[account(
+        constraint = !feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        mut,
+        has_one=feed,
+        constraint = report_record.load()?.slashed @ StarboardError::NotSlashed,
+    )]
+    pub report_record: AccountLoader<'info, ReportRecord>,
+    #[account(
+        mut,
+        has_one=report_record,
+        has_one=certifier,
+        close=certifier,
+    )]
+    pub cert_record: AccountLoader<'info, CertRecord>,
+    #[account(
+        mut,
+        address = cert_record.load()?.escrow,
+    )]
+    pub certifier_escrow: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = feed.load()?.vault,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    /// CHECK: receives the rent of the closed certification accounts
+    #[account(mut)]
+    pub certifier: UncheckedAccount<'info>,
+    /// CHECK: program as signer
+    #[account(seeds=[b"program",b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Crank<'info> {
+    #[account(mut)]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        mut,
+        seeds = [ROUND_SEED, feed.key().as_ref(), &[0]],
+        bump = round_0.load()?.bump,
+    )]
+    pub round_0: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        seeds = [ROUND_SEED, feed.key().as_ref(), &[1]],
+        bump = round_1.load()?.bump,
+    )]
+    pub round_1: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        seeds = [ROUND_SEED, feed.key().as_ref(), &[2]],
+        bump = round_2.load()?.bump,
+    )]
+    pub round_2: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        seeds = [ROUND_SEED, feed.key().as_ref(), &[3]],
+        bump = round_3.load()?.bump,
+    )]
+    pub round_3: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        seeds = [ROUND_SEED, feed.key().as_ref(), &[4]],
+        bump = round_4.load()?.bump,
+    )]
+    pub round_4: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        token::mint = stake_mint,
+    )]
+    pub keeper_tokens: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = feed.load()?.lease,
+    )]
+    pub lease: Account<'info, TokenAccount>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
+    /// CHECK: program as signer
+    #[account(seeds=[b"program",b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct StakeNative<'info> {
+    #[account(
+        init,
+        seeds = [
+            ESCROW_SEED,
+            voter.key().as_ref(),
+            round.key().as_ref(),
+        ],
+        bump,
+        space = 8 + std::mem::size_of::<Escrow>(),
+        payer = voter,
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    #[account(
+        constraint = feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        mut,
+        has_one = feed
+    )]
+    pub round: AccountLoader<'info, Round>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TopUpStakeNative<'info> {
+    #[account(
+        mut,
+        seeds = [
+            ESCROW_SEED,
+            voter.key().as_ref(),
+            round.key().as_ref(),
+        ],
+        bump = escrow.load()?.bump,
+        constraint = escrow.load()?.staker == voter.key(),
+        has_one = feed,
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    #[account(
+        constraint = feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        has_one = feed
+    )]
+    pub round: AccountLoader<'info, Round>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReportNative<'info> {
+    #[account(
+        constraint = feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        mut,
+        has_one=feed,
+    )]
+    pub round: AccountLoader<'info, Round>,
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+    #[account(
+        seeds = [
+            ESCROW_SEED,
+            reporter.key().as_ref(),
+            round.key().as_ref(),
+        ],
+        bump = escrow.load()?.bump,
+        has_one = feed,
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+    #[account(
+        init,
+        seeds=[
+            REPORT_RECORD_SEED,
+            round.key().as_ref(),
+            reporter.key().as_ref(),
+        ],
+        bump,
+        payer=reporter,
+        space = 8 + std::mem::size_of::<ReportRecord>(),
+    )]
+    pub report_record: AccountLoader<'info, ReportRecord>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CertifyNative<'info> {
+    #[account(
+        constraint = feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        mut,
+        has_one=feed,
+    )]
+    pub round: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        has_one=round,
+        has_one=feed,
+    )]
+    pub report_record: AccountLoader<'info, ReportRecord>,
+    #[account(mut)]
+    pub certifier: Signer<'info>,
+    #[account(
+        init,
+        seeds=[
+            CERT_RECORD_SEED,
+            report_record.key().as_ref(),
+            certifier.key().as_ref(),
+        ],
+        bump,
+        payer=certifier,
+        space = 8 + std::mem::size_of::<CertRecord>(),
+    )]
+    pub cert_record: AccountLoader<'info, CertRecord>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawStakeNative<'info> {
+    #[account(
+        constraint = feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        has_one = feed
+    )]
+    pub round: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        seeds = [
+            ESCROW_SEED,
+            staker.key().as_ref(),
+            round.key().as_ref(),
+        ],
+        bump = escrow.load()?.bump,
+        has_one = staker,
+        has_one = feed,
+        close = staker,
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+    /// CHECK: the staker's ReportRecord for this round, possibly uninitialized
+    #[account(
+        seeds=[
+            REPORT_RECORD_SEED,
+            round.key().as_ref(),
+            staker.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub report_record: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub staker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewardsNative<'info> {
+    #[account(
+        mut,
+        constraint = feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        mut,
+        has_one=feed,
+        has_one=reporter,
+    )]
+    pub report_record: AccountLoader<'info, ReportRecord>,
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+    #[account(
+        init,
+        seeds=[
+            PAYOUT_SEED,
+            report_record.key().as_ref(),
+        ],
+        bump,
+        payer = reporter,
+        token::mint = stake_mint,
+        token::authority = program_as_signer,
+    )]
+    pub payout: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = feed.load()?.vault,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
+    /// CHECK: program as signer
+    #[account(seeds=[b"program",b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimCertRewardsNative<'info> {
+    #[account(
+        mut,
+        constraint = feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        has_one=feed,
+    )]
+    pub round: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        has_one=feed,
+        has_one=round,
+    )]
+    pub report_record: AccountLoader<'info, ReportRecord>,
+    #[account(
+        mut,
+        has_one=report_record,
+        has_one=certifier,
+        close=certifier,
+    )]
+    pub cert_record: AccountLoader<'info, CertRecord>,
+    #[account(mut)]
+    pub certifier: Signer<'info>,
+    #[account(
+        init,
+        seeds=[
+            PAYOUT_SEED,
+            cert_record.key().as_ref(),
+        ],
+        bump,
+        payer = certifier,
+        token::mint = stake_mint,
+        token::authority = program_as_signer,
+    )]
+    pub payout: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = feed.load()?.vault,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(address = feed.load()?.stake_mint)]
+    pub stake_mint: Account<'info, Mint>,
+    /// CHECK: program as signer
+    #[account(seeds=[b"program",b"signer"], bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseReportNative<'info> {
+    #[account(
+        constraint = feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        has_one = feed
+    )]
+    pub round: AccountLoader<'info, Round>,
+    #[account(
+        mut,
+        has_one=feed,
+        has_one=round,
+        has_one=reporter,
+        close=reporter,
+    )]
+    pub report_record: AccountLoader<'info, ReportRecord>,
+    /// CHECK: the reporter's Escrow, which must be closed if it was slashed
+    #[account(address = report_record.load()?.escrow)]
+    pub escrow: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SlashReporterNative<'info> {
+    #[account(
+        constraint = feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        has_one=feed,
+        has_one=escrow,
+        constraint = report_record.load()?.slashed @ StarboardError::NotSlashed,
+    )]
+    pub report_record: AccountLoader<'info, ReportRecord>,
+    #[account(
+        mut,
+        has_one=staker,
+        close=staker,
+        constraint = escrow.load()?.round_height == report_record.load()?.round_height
+            @ StarboardError::EscrowRoundMismatch,
+    )]
+    pub escrow: AccountLoader<'info, Escrow>,
+    #[account(
+        mut,
+        address = feed.load()?.vault,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    /// CHECK: receives the rent of the closed escrow
+    #[account(mut)]
+    pub staker: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SlashCertifierNative<'info> {
+    #[account(
+        constraint = feed.load()?.native_stake @ StarboardError::WrongStakeMode,
+    )]
+    pub feed: AccountLoader<'info, Feed>,
+    #[account(
+        seeds = [STARBOARD_SEED],
+        bump = starboard.load()?.bump,
+        constraint = !starboard.load()?.paused @ StarboardError::ProtocolPaused,
+        constraint = !feed.load()?.paused @ StarboardError::FeedPaused,
+    )]
+    pub starboard: AccountLoader<'info, Starboard>,
+    #[account(
+        mut,
+        has_one=feed,
+        constraint = report_record.load()?.slashed @ StarboardError::NotSlashed,
+    )]
+    pub report_record: AccountLoader<'info, ReportRecord>,
+    #[account(
+        mut,
+        has_one=report_record,
+        has_one=certifier,
+        close=certifier,
+    )]
+    pub cert_record: AccountLoader<'info, CertRecord>,
+    #[account(
+        mut,
+        address = feed.load()?.vault,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    /// CHECK: receives the rent of the closed CertRecord
+    #[account(mut)]
+    pub certifier: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,309 @@
+use crate::*;
+use anchor_spl::token::{TokenAccount, Token, SyncNative, sync_native, Transfer, transfer, CloseAccount, close_account};
+
+fn require_staking(round: &Round, round_key: Pubkey, feed: &Feed, timestamp: u64) -> Result<()> {
+    round.require_stage(StageKind::Staking)?;
+    let round_is_staking =
+        feed.staking_round == Some(round_key) &&
+        round.staking_start_timestamp.checked_add(round.update_interval.into()).unwrap() > timestamp;
+    require!(round_is_staking, StarboardError::RoundNotStaking);
+    Ok(())
+}
+
+/// Fails unless `total` is at least the round's minimum stake and within
+/// its per-staker cap, if it has one.
+fn require_stake_in_range(round: &Round, total: u64) -> Result<()> {
+    require!(total >= round.min_stake, StarboardError::StakeBelowMinimum);
+    require!(
+        round.max_stake == 0 || total <= round.max_stake,
+        StarboardError::StakeAboveCap
+    );
+    Ok(())
+}
+
+/// A deposit opening an escrow.
+pub struct StakeArgs {
+    pub staker: Pubkey,
+    // where the stake is held: the escrow token account, or the Escrow
+    // itself in native mode
+    pub token_account: Pubkey,
+    pub amount: u64,
+    pub timestamp: u64,
+}
+
+/// Fills a new Escrow for the staker on the round currently staking and
+/// counts the staker. `stake` and `stake_native` then move the amount
+/// into `args.token_account`.
+pub fn open_escrow(
+    escrow: &mut Escrow,
+    round: &mut Round,
+    round_key: Pubkey,
+    feed: &Feed,
+    feed_key: Pubkey,
+    args: StakeArgs,
+) -> Result<()> {
+    require_staking(round, round_key, feed, args.timestamp)?;
+    require_stake_in_range(round, args.amount)?;
+
+    escrow.round_height = round.round_height;
+    escrow.staker = args.staker;
+    escrow.feed = feed_key;
+    escrow.mint = feed.stake_mint;
+    escrow.token_account = args.token_account;
+    escrow.timestamp = args.timestamp;
+    escrow.amount = args.amount;
+    round.num_stakers = round.num_stakers.checked_add(1).unwrap();
+    Ok(())
+}
+
+/// Adds `amount` to an escrow while its round is still staking. The
+/// total stays subject to the round's minimum and cap.
+pub fn top_up_escrow(
+    escrow: &mut Escrow,
+    round: &Round,
+    round_key: Pubkey,
+    feed: &Feed,
+    amount: u64,
+    timestamp: u64,
+) -> Result<()> {
+    require_staking(round, round_key, feed, timestamp)?;
+    require!(
+        escrow.round_height == round.round_height,
+        StarboardError::EscrowRoundMismatch
+    );
+    let total = escrow.amount.checked_add(amount).unwrap();
+    require_stake_in_range(round, total)?;
+    escrow.amount = total;
+    Ok(())
+}
+
+/// Fails unless the round `escrow` staked on has settled and the staker's
+/// ReportRecord for that height, if it has one, was not slashed.
+pub fn require_withdrawable(escrow: &Escrow, round: &Round, report_record_info: &AccountInfo) -> Result<()> {
+    require!(
+        round.is_settled(escrow.round_height),
+        StarboardError::RoundNotSettled
+    );
+    if !report_record_info.data_is_empty() {
+        let report_record = AccountLoader::<ReportRecord>::try_from(report_record_info)?;
+        let report_record = report_record.load()?;
+        require!(
+            report_record.round_height != escrow.round_height || !report_record.slashed,
+            StarboardError::StakeSlashed
+        );
+    }
+    Ok(())
+}
+
+/// Checks that `escrow` may report in `round` now and that the round has
+/// room for another report, and counts the report on the round.
+pub fn record_report(feed: &Feed, round: &mut Round, round_key: Pubkey, escrow: &Escrow, timestamp: u64) -> Result<()> {
+    round.require_stage(StageKind::Reporting)?;
+    let round_is_reporting =
+        feed.reporting_round == Some(round_key) &&
+        round.reporting_start_timestamp.checked_add(round.update_interval.into()).unwrap() > timestamp;
+    require!(round_is_reporting, StarboardError::RoundNotReporting);
+    require!(
+        escrow.round_height == round.round_height,
+        StarboardError::EscrowRoundMismatch
+    );
+    require!(round.num_reports < round.max_reporters, StarboardError::RoundFull);
+    round.num_reports = round.num_reports.checked_add(1).unwrap();
+    Ok(())
+}
+
+impl ReportRecord {
+    pub fn fill(&mut self, round: &Round, round_key: Pubkey, escrow: &Escrow, escrow_key: Pubkey, timestamp: u64) {
+        self.timestamp = timestamp;
+        self.round_height = round.round_height;
+        self.round = round_key;
+        self.feed = escrow.feed;
+        self.reporter = escrow.staker;
+        self.escrow = escrow_key;
+        self.stake = escrow.amount;
+        // the value stays sealed until it is revealed against the
+        // reporter's commitment during the certifying stage
+        self.value = 0;
+        self.confidence = 0;
+        self.revealed = false;
+    }
+}
+
+/// Checks that `certifier` may certify `report_record` now and books the
+/// certification on the report and round. Returns the bond to post.
+pub fn record_certification(
+    feed: &Feed,
+    round: &mut Round,
+    round_key: Pubkey,
+    report_record: &mut ReportRecord,
+    certifier: Pubkey,
+    timestamp: u64,
+) -> Result<u64> {
+    round.require_stage(StageKind::Certifying)?;
+    let round_is_certifying =
+        feed.certifying_round == Some(round_key) &&
+        round.certifying_start_timestamp.checked_add(round.update_interval.into()).unwrap() > timestamp;
+    require!(round_is_certifying, StarboardError::RoundNotCertifying);
+    require!(
+        report_record.round_height == round.round_height,
+        StarboardError::EscrowRoundMismatch
+    );
+    require!(report_record.revealed, StarboardError::ReportNotRevealed);
+    require!(
+        report_record.reporter != certifier,
+        StarboardError::SelfCertification
+    );
+
+    if report_record.num_certifications == 0 {
+        round.num_certified = round.num_certified.checked_add(1).unwrap();
+    }
+    report_record.num_certifications = report_record.num_certifications.checked_add(1).unwrap();
+    report_record.cert_bond_total = report_record.cert_bond_total.checked_add(feed.cert_bond).unwrap();
+    Ok(feed.cert_bond)
+}
+
+impl CertRecord {
+    pub fn fill(
+        &mut self,
+        report_record: &ReportRecord,
+        report_record_key: Pubkey,
+        certifier: Pubkey,
+        escrow: Pubkey,
+        bond: u64,
+        timestamp: u64,
+    ) {
+        self.timestamp = timestamp;
+        self.round_height = report_record.round_height;
+        self.round = report_record.round;
+        self.feed = report_record.feed;
+        self.reporter = report_record.reporter;
+        self.escrow = escrow;
+        self.value = report_record.value;
+        self.confidence = report_record.confidence;
+        self.certifier = certifier;
+        self.report_record = report_record_key;
+        self.bond = bond;
+    }
+}
+
+/// Marks an honest reporter's reward as claimed and returns it. Reports
+/// nobody certified were left out of the round and earn nothing.
+pub fn claim_reporter_reward(feed: &mut Feed, report_record: &mut ReportRecord) -> Result<u64> {
+    require!(
+        report_record.finalized && !report_record.slashed,
+        StarboardError::NoRewards
+    );
+    require!(
+        report_record.num_certifications > 0,
+        StarboardError::ReportNotCertified
+    );
+    require!(!report_record.reward_claimed, StarboardError::RewardsAlreadyClaimed);
+    report_record.reward_claimed = true;
+    feed.rewards_owed = feed.rewards_owed.checked_sub(report_record.reporter_reward).unwrap();
+    Ok(report_record.reporter_reward)
+}
+
+/// Checks that a ReportRecord may be closed: its round has settled, every
+/// certification is settled, and its reward claimed or its slashed stake
+/// collected. Uncertified reports earned nothing and have nothing to claim.
+pub fn require_closable(round: &Round, report_record: &ReportRecord, escrow_info: &AccountInfo) -> Result<()> {
+    require!(
+        round.is_settled(report_record.round_height),
+        StarboardError::RoundNotSettled
+    );
+    require!(
+        report_record.certs_settled == report_record.num_certifications,
+        StarboardError::CertificationsNotSettled
+    );
+    let honest = report_record.finalized && !report_record.slashed &&
+        report_record.num_certifications > 0;
+    require!(
+        !honest || report_record.reward_claimed,
+        StarboardError::RewardsNotClaimed
+    );
+    require!(
+        !report_record.slashed || escrow_info.data_is_empty(),
+        StarboardError::SlashNotCollected
+    );
+    Ok(())
+}
+
+/// Settles one certification of an honest or failed report and returns
+/// the certifier's reward. Certifiers behind reports of a failed round
+/// were never finalized and get their bond back without a reward.
+pub fn settle_certification(
+    feed: &mut Feed,
+    round: &Round,
+    report_record: &mut ReportRecord,
+    cert_record: &CertRecord,
+) -> Result<u64> {
+    let round_failed = !report_record.finalized && round.is_settled(report_record.round_height);
+    require!(
+        (report_record.finalized && !report_record.slashed) || round_failed,
+        StarboardError::NoRewards
+    );
+    let reward = if round_failed {
+        0
+    } else {
+        pro_rata(report_record.cert_reward, cert_record.bond, report_record.cert_bond_total)
+    };
+    report_record.certs_settled = report_record.certs_settled.checked_add(1).unwrap();
+    feed.rewards_owed = feed.rewards_owed.checked_sub(reward).unwrap();
+    Ok(reward)
+}
+
+/// Debits `amount` lamports from an account owned by this program.
+pub fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    let from_lamports = from.lamports().checked_sub(amount).unwrap();
+    let to_lamports = to.lamports().checked_add(amount).unwrap();
+    **from.try_borrow_mut_lamports()? = from_lamports;
+    **to.try_borrow_mut_lamports()? = to_lamports;
+    Ok(())
+}
+
+/// Moves native stake or bond lamports into the feed's wrapped SOL vault,
+/// so slashed native stake lands in the same reward pool as SPL stake.
+pub fn sweep_into_vault<'info>(
+    from: &AccountInfo<'info>,
+    vault: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    move_lamports(from, &vault.to_account_info(), amount)?;
+    let sync_accounts = SyncNative {
+        account: vault.to_account_info(),
+    };
+    sync_native(CpiContext::new(token_program.to_account_info(), sync_accounts))
+}
+
+/// Pays `amount` out of a native feed's wrapped SOL vault as plain
+/// lamports: the wSOL goes through `payout`, a token account created for
+/// the claim, which is then closed to `recipient` together with its rent,
+/// so native users never need a wSOL account of their own.
+pub fn pay_out_native<'info>(
+    vault: &Account<'info, TokenAccount>,
+    payout: &Account<'info, TokenAccount>,
+    recipient: &AccountInfo<'info>,
+    program_as_signer: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[&[b"program", b"signer", &[bump]]];
+    if amount > 0 {
+        let transfer_accounts = Transfer {
+            from: vault.to_account_info(),
+            to: payout.to_account_info(),
+            authority: program_as_signer.clone(),
+        };
+        let cpi_context = CpiContext::new_with_signer(token_program.to_account_info(), transfer_accounts, signer_seeds);
+        transfer(cpi_context, amount)?;
+    }
+    let close_accounts = CloseAccount {
+        account: payout.to_account_info(),
+        destination: recipient.clone(),
+        authority: program_as_signer.clone(),
+    };
+    let cpi_context = CpiContext::new_with_signer(token_program.to_account_info(), close_accounts, signer_seeds);
+    close_account(cpi_context)
+}
@@ -1,5 +1,10 @@
+// every Anchor instruction returns anchor_lang::error::Error, which is large by design
+#![allow(clippy::result_large_err)]
+
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::msg;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::system_program;
 use std::ops::DerefMut;
 use solana_program::pubkey;
 use anchor_spl::{
@@ -9,7 +14,9 @@ use anchor_spl::{
         Mint, 
         Token,
         Transfer,
-        transfer
+        transfer,
+        CloseAccount,
+        close_account,
     },
     associated_token::{
         AssociatedToken
@@ -18,32 +25,157 @@ use anchor_spl::{
 
 mod contexts;
 use contexts::*;
+mod stage;
+pub use stage::*;
+mod pipeline;
+pub use pipeline::*;
+mod escrow;
+pub use escrow::*;
+mod read;
+pub use read::*;
+mod aggregate;
+pub use aggregate::*;
+mod config;
+pub use config::*;
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
+const STARBOARD_SEED: &[u8] = b"Starboard";
 const FEED_SEED: &[u8] = b"Feed";
 const ROUND_SEED: &[u8] = b"Round";
 const ESCROW_SEED: &[u8] = b"Escrow";
 const ESCROW_TOKEN_SEED: &[u8] = b"EscrowToken";
 const REPORT_RECORD_SEED: &[u8] = b"ReportRecordSeed";
 const CERT_RECORD_SEED: &[u8] = b"CertRecordSeed";
+const FEED_VAULT_SEED: &[u8] = b"FeedVault";
+const FEED_LEASE_SEED: &[u8] = b"FeedLease";
+const PAYOUT_SEED: &[u8] = b"Payout";
+const COMMITMENT_DOMAIN: &[u8] = b"StarboardCommitment";
 const NATIVE_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
+// bumped whenever the Feed layout changes, so readers that decode it by
+// hand, like the starboard-consumer crate, can tell layouts apart
+const FEED_LAYOUT_VERSION: u8 = 4;
+const BPS_DENOMINATOR: u128 = 10_000;
+// a stage that has not advanced after this many update intervals can be failed
+const STAGE_DEADLINE_INTERVALS: u64 = 2;
+// finalize takes every ReportRecord of a round in one transaction, next
+// to its own accounts; more than this would not fit
+const MAX_REPORTERS: u32 = 20;
 
 #[program]
 pub mod starboard {
     use super::*;
 
+    /// Creates the protocol-wide config. The signer becomes its admin.
+    pub fn init_starboard(
+        ctx: Context<InitStarboard>,
+        treasury: Pubkey,
+        protocol_fee_bps: u32,
+        default_stake_mint: Pubkey,
+        default_slash_band_bps: u32,
+        default_slash_mad_multiple: u32,
+    ) -> Result<()> {
+        let mut starboard = ctx.accounts.starboard.load_init()?;
+        starboard.admin = ctx.accounts.admin.key();
+        starboard.treasury = treasury;
+        starboard.default_stake_mint = default_stake_mint;
+        starboard.protocol_fee_bps = protocol_fee_bps;
+        starboard.default_slash_band_bps = default_slash_band_bps;
+        starboard.default_slash_mad_multiple = default_slash_mad_multiple;
+        starboard.paused = false;
+        starboard.bump = *ctx.bumps.get("starboard").unwrap();
+        require!(
+            u128::from(starboard.protocol_fee_bps) <= BPS_DENOMINATOR,
+            StarboardError::InvalidProtocolConfig
+        );
+        Ok(())
+    }
+
+    /// Updates the protocol-wide config. Fields left as None are unchanged.
+    pub fn update_starboard(
+        ctx: Context<UpdateStarboard>,
+        admin: Option<Pubkey>,
+        treasury: Option<Pubkey>,
+        protocol_fee_bps: Option<u32>,
+        default_stake_mint: Option<Pubkey>,
+        default_slash_band_bps: Option<u32>,
+        default_slash_mad_multiple: Option<u32>,
+    ) -> Result<()> {
+        let mut starboard = ctx.accounts.starboard.load_mut()?;
+        if let Some(admin) = admin {
+            starboard.admin = admin;
+        }
+        if let Some(treasury) = treasury {
+            starboard.treasury = treasury;
+        }
+        if let Some(protocol_fee_bps) = protocol_fee_bps {
+            starboard.protocol_fee_bps = protocol_fee_bps;
+        }
+        if let Some(default_stake_mint) = default_stake_mint {
+            starboard.default_stake_mint = default_stake_mint;
+        }
+        if let Some(default_slash_band_bps) = default_slash_band_bps {
+            starboard.default_slash_band_bps = default_slash_band_bps;
+        }
+        if let Some(default_slash_mad_multiple) = default_slash_mad_multiple {
+            starboard.default_slash_mad_multiple = default_slash_mad_multiple;
+        }
+        require!(
+            u128::from(starboard.protocol_fee_bps) <= BPS_DENOMINATOR,
+            StarboardError::InvalidProtocolConfig
+        );
+        Ok(())
+    }
+
+    /// Halts every feed. Only withdrawals keep working while paused.
+    pub fn pause_protocol(ctx: Context<SetProtocolPause>) -> Result<()> {
+        ctx.accounts.starboard.load_mut()?.paused = true;
+        msg!("Protocol paused");
+        Ok(())
+    }
+
+    pub fn unpause_protocol(ctx: Context<SetProtocolPause>) -> Result<()> {
+        ctx.accounts.starboard.load_mut()?.paused = false;
+        msg!("Protocol unpaused");
+        Ok(())
+    }
+
+    /// Halts a single feed, by its authority or the protocol admin.
+    pub fn pause_feed(ctx: Context<SetFeedPause>) -> Result<()> {
+        ctx.accounts.feed.load_mut()?.paused = true;
+        msg!("Feed {} paused", ctx.accounts.feed.key());
+        Ok(())
+    }
+
+    pub fn unpause_feed(ctx: Context<SetFeedPause>) -> Result<()> {
+        ctx.accounts.feed.load_mut()?.paused = false;
+        msg!("Feed {} unpaused", ctx.accounts.feed.key());
+        Ok(())
+    }
+
     pub fn init_feed(
         ctx: Context<InitFeed>,
         id: [u8; 32],
-        description: [u8; 32],
-        update_interval: u32,
+        params: FeedParams,
     ) -> Result<()> {
+        require!(
+            params.custom_stake_mint ||
+                ctx.accounts.stake_mint.key() == ctx.accounts.starboard.load()?.default_stake_mint,
+            StarboardError::StakeMintNotDefault
+        );
+        require!(
+            !params.native_stake || ctx.accounts.stake_mint.key() == NATIVE_MINT,
+            StarboardError::NativeStakeNeedsNativeMint
+        );
         let mut feed = ctx.accounts.feed.load_init()?;
-        feed.description = description;
+        feed.version = FEED_LAYOUT_VERSION;
+        feed.description = params.description;
+        feed.exponent = params.exponent;
         feed.latest_finalized_value = 0;
+        feed.latest_finalized_confidence = 0;
         feed.latest_finalized_timestamp = 0;
-        feed.update_interval = update_interval;
+        feed.latest_finalized_round_height = 0;
+        feed.update_interval = params.update_interval;
         feed.staking_round = None;
         feed.reporting_round = None;
         feed.committing_round = None;
@@ -52,7 +184,28 @@ pub mod starboard {
         feed.bump = *ctx.bumps.get("feed").unwrap();
         feed.started = false;
         feed.height = 0;
-        feed.min_stake = 5;
+        feed.min_stake = params.min_stake;
+        feed.max_stake = params.max_stake;
+        feed.cert_bond = params.cert_bond;
+        feed.vault = ctx.accounts.vault.key();
+        feed.stake_mint = ctx.accounts.stake_mint.key();
+        feed.lease = ctx.accounts.lease.key();
+        feed.lease_authority = ctx.accounts.payer.key();
+        let starboard = ctx.accounts.starboard.load()?;
+        feed.slash_band_bps = params.slash_band_bps.unwrap_or(starboard.default_slash_band_bps);
+        feed.slash_mad_multiple = params.slash_mad_multiple.unwrap_or(starboard.default_slash_mad_multiple);
+        feed.reward_per_round = params.reward_per_round;
+        feed.rewards_owed = 0;
+        feed.min_reporters = params.min_reporters;
+        feed.max_reporters = params.max_reporters;
+        feed.stale = false;
+        feed.crank_bounty = params.crank_bounty;
+        feed.authority = ctx.accounts.payer.key();
+        feed.paused = false;
+        feed.native_stake = params.native_stake;
+        feed.aggregation = params.aggregation;
+        feed.trim_bps = params.trim_bps;
+        require!(feed.config_is_valid(), StarboardError::InvalidFeedConfig);
         Ok(())
     }
 
@@ -62,9 +215,8 @@ pub mod starboard {
     ) -> Result<()> {
         let round_key = ctx.accounts.round.key().clone();
         let mut round = ctx.accounts.round.load_init()?;
-        //round.current_stage = StageKind::Standby;
         round.feed = ctx.accounts.feed.key().clone();
-        round.current_stage = 0;
+        round.current_stage = StageKind::Standby as u8;
         round.staking_start_timestamp = 0;
         round.num_stakers = 0;
         round.reporting_start_timestamp = 0;
@@ -74,6 +226,17 @@ pub mod starboard {
         round.bump = *ctx.bumps.get("round").unwrap();
         round.num = num;
         round.round_height = 0;
+        round.num_certified = 0;
+        round.bounties_paid = 0;
+        round.update_interval = 0;
+        round.min_stake = 0;
+        round.min_reporters = 0;
+        round.max_reporters = 0;
+        round.slash_band_bps = 0;
+        round.slash_mad_multiple = 0;
+        round.max_stake = 0;
+        round.aggregation = 0;
+        round.trim_bps = 0;
         let mut feed = ctx.accounts.feed.load_mut()?;
         match num {
             0 => {
@@ -112,70 +275,193 @@ pub mod starboard {
         Ok(())
     }
 
-    pub fn start_staking(
-        ctx: Context<StartStaking>,
+    /// Updates the feed's parameters. Fields left as None are unchanged.
+    /// Round parameters take effect when the next round starts staking.
+    pub fn set_feed_config(
+        ctx: Context<SetFeedConfig>,
+        update: FeedConfigUpdate,
     ) -> Result<()> {
+        let mut feed = ctx.accounts.feed.load_mut()?;
+        feed.apply_config(&update);
+        require!(feed.config_is_valid(), StarboardError::InvalidFeedConfig);
+        msg!("Feed config updated, applies from height {}", feed.height.checked_add(1).unwrap());
+        Ok(())
+    }
+
+    pub fn transfer_feed_authority(ctx: Context<TransferFeedAuthority>, new_authority: Pubkey) -> Result<()> {
+        let mut feed = ctx.accounts.feed.load_mut()?;
+        feed.authority = new_authority;
+        msg!("Feed authority transferred to {}", new_authority);
+        Ok(())
+    }
+
+    /// Sponsors the feed by moving tokens into its lease. Anyone can fund
+    /// a lease.
+    pub fn fund_lease(ctx: Context<FundLease>, amount: u64) -> Result<()> {
+        let transfer_accounts = Transfer {
+            from: ctx.accounts.funder_tokens.to_account_info(),
+            to: ctx.accounts.lease.to_account_info(),
+            authority: ctx.accounts.funder.to_account_info(),
+        };
+        let cpi_context = CpiContext::new(ctx.accounts.token_program.to_account_info(), transfer_accounts);
+        transfer(cpi_context, amount)?;
+        msg!("Funded lease with {} from {}", amount, ctx.accounts.funder.key());
+        Ok(())
+    }
+
+    pub fn withdraw_lease(ctx: Context<WithdrawLease>, amount: u64) -> Result<()> {
+        require!(ctx.accounts.lease.amount >= amount, StarboardError::InsufficientLease);
+        let bump = *ctx.bumps.get("program_as_signer").unwrap();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"program", b"signer", &[bump]]];
+        let transfer_accounts = Transfer {
+            from: ctx.accounts.lease.to_account_info(),
+            to: ctx.accounts.recipient_tokens.to_account_info(),
+            authority: ctx.accounts.program_as_signer.to_account_info(),
+        };
+        let cpi_context = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), transfer_accounts, signer_seeds);
+        transfer(cpi_context, amount)?;
+        msg!("Withdrew {} from lease", amount);
+        Ok(())
+    }
+
+    pub fn start_staking(ctx: Context<StartStaking>) -> Result<()> {
         let clock = Clock::get()?;
         let timestamp = clock.slot;
         let round_key = ctx.accounts.round.key().clone();
+        let feed_key = ctx.accounts.feed.key();
         let mut feed = ctx.accounts.feed.load_mut()?;
         let mut round = ctx.accounts.round.load_mut()?;
 
-        // 0 = standby
+        let old_round = previous_round(&feed, feed_key, StageKind::Staking, round_key, ctx.remaining_accounts)?;
         require!(
-            round.current_stage == 0,
-            StarboardError::RoundNotReady
+            ctx.accounts.lease.amount >= feed.round_cost(),
+            StarboardError::LeaseUnderfunded
         );
+        advance(&mut feed, &mut round, round_key, StageKind::Staking, old_round.as_ref(), timestamp)?;
 
-        if feed.staking_round != Some(round_key) {
-            if let Some(_staking_round) = feed.staking_round {
-                let old_round = AccountLoader::<Round>::try_from(&ctx.remaining_accounts[0])?;
-                let old_round_data = old_round.load()?;
-                let feed_not_staking = (old_round_data.staking_start_timestamp.checked_add(feed.update_interval.into()).unwrap()) < timestamp;
-                require!(feed_not_staking, StarboardError::StakingInProgress);
-            }
-        }
-
-        feed.staking_round = Some(round_key);
-        round.current_stage = 1;
-        round.staking_start_timestamp = timestamp;
-        msg!("feed height: {}", feed.height);
-        feed.height = feed.height.checked_add(1u64).unwrap();
-        round.round_height = feed.height;
-        msg!("feed height: {}", feed.height);
-        msg!("round height: {}", round.round_height);
-        msg!("Staking round started at {}", timestamp);
-
-        Ok(())
+        let mut available = ctx.accounts.lease.amount;
+        let bounty = crank_bounty(&feed, &mut round, StageKind::Staking, &mut available);
+        pay_crank_bounty(
+            &ctx.accounts.lease,
+            &ctx.accounts.keeper_tokens,
+            &ctx.accounts.program_as_signer,
+            &ctx.accounts.token_program,
+            *ctx.bumps.get("program_as_signer").unwrap(),
+            bounty,
+        )
     }
 
+    /// Opens an escrow on the round currently staking with `amount`,
+    /// which must be at least the round's minimum stake and within its cap.
     pub fn stake(
         ctx: Context<Stake>,
+        amount: u64,
     ) -> Result<()> {
         let clock = Clock::get()?;
         let timestamp = clock.slot;
         let mut escrow = ctx.accounts.escrow.load_init()?;
         let round_key = ctx.accounts.round.key().clone();
         let mut round = ctx.accounts.round.load_mut()?;
-        escrow.round_height = round.round_height;
-        escrow.staker = ctx.accounts.voter.key();
-        escrow.feed = ctx.accounts.feed.key();
+        let feed = ctx.accounts.feed.load()?;
         escrow.bump = *ctx.bumps.get("escrow").unwrap();
-        escrow.timestamp = timestamp;
-        let feed = ctx.accounts.feed.load_mut()?;
-        let round_is_staking = 
-            feed.staking_round == Some(round_key) &&
-            round.current_stage == 1 &&
-            round.staking_start_timestamp.checked_add(feed.update_interval.into()).unwrap() > timestamp;
-        require!(round_is_staking, StarboardError::RoundNotStaking);
-        round.num_stakers = round.num_stakers.checked_add(1).unwrap();
+        open_escrow(
+            &mut escrow,
+            &mut round,
+            round_key,
+            &feed,
+            ctx.accounts.feed.key(),
+            StakeArgs {
+                staker: ctx.accounts.voter.key(),
+                token_account: ctx.accounts.escrow_token.key(),
+                amount,
+                timestamp,
+            },
+        )?;
+        let transfer_accounts = Transfer {
+            from: ctx.accounts.voter_token_account.to_account_info(),
+            to: ctx.accounts.escrow_token.to_account_info(),
+            authority: ctx.accounts.voter.to_account_info(),
+        };
+        let cpi_context = CpiContext::new(ctx.accounts.token_program.to_account_info(), transfer_accounts);
+        transfer(cpi_context, amount)?;
+        Ok(())
+    }
+
+    /// Adds `amount` to the staker's escrow while its round is still staking.
+    pub fn top_up_stake(ctx: Context<TopUpStake>, amount: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        let timestamp = clock.slot;
+        let mut escrow = ctx.accounts.escrow.load_mut()?;
+        let round = ctx.accounts.round.load()?;
+        let feed = ctx.accounts.feed.load()?;
+        top_up_escrow(&mut escrow, &round, ctx.accounts.round.key(), &feed, amount, timestamp)?;
         let transfer_accounts = Transfer {
             from: ctx.accounts.voter_token_account.to_account_info(),
             to: ctx.accounts.escrow_token.to_account_info(),
             authority: ctx.accounts.voter.to_account_info(),
         };
         let cpi_context = CpiContext::new(ctx.accounts.token_program.to_account_info(), transfer_accounts);
-        transfer(cpi_context, feed.min_stake)?;
+        transfer(cpi_context, amount)?;
+        msg!("Stake topped up to {}", escrow.amount);
+        Ok(())
+    }
+
+    /// `stake` for feeds in native mode: the stake is paid in lamports
+    /// straight into the Escrow PDA, no token accounts involved.
+    pub fn stake_native(ctx: Context<StakeNative>, amount: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        let timestamp = clock.slot;
+        // the escrow must not stay borrowed into the transfer below, which
+        // borrows the data of every writable account it is passed
+        {
+            let mut escrow = ctx.accounts.escrow.load_init()?;
+            let round_key = ctx.accounts.round.key();
+            let mut round = ctx.accounts.round.load_mut()?;
+            let feed = ctx.accounts.feed.load()?;
+            escrow.bump = *ctx.bumps.get("escrow").unwrap();
+            open_escrow(
+                &mut escrow,
+                &mut round,
+                round_key,
+                &feed,
+                ctx.accounts.feed.key(),
+                StakeArgs {
+                    staker: ctx.accounts.voter.key(),
+                    token_account: ctx.accounts.escrow.key(),
+                    amount,
+                    timestamp,
+                },
+            )?;
+        }
+        let transfer_accounts = system_program::Transfer {
+            from: ctx.accounts.voter.to_account_info(),
+            to: ctx.accounts.escrow.to_account_info(),
+        };
+        let cpi_context = CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_accounts);
+        system_program::transfer(cpi_context, amount)?;
+        msg!("Staked {} lamports", amount);
+        Ok(())
+    }
+
+    /// `top_up_stake` for feeds in native mode.
+    pub fn top_up_stake_native(ctx: Context<TopUpStakeNative>, amount: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        let timestamp = clock.slot;
+        // released before the transfer into the escrow, see `stake_native`
+        let total = {
+            let mut escrow = ctx.accounts.escrow.load_mut()?;
+            let round = ctx.accounts.round.load()?;
+            let feed = ctx.accounts.feed.load()?;
+            top_up_escrow(&mut escrow, &round, ctx.accounts.round.key(), &feed, amount, timestamp)?;
+            escrow.amount
+        };
+        let transfer_accounts = system_program::Transfer {
+            from: ctx.accounts.voter.to_account_info(),
+            to: ctx.accounts.escrow.to_account_info(),
+        };
+        let cpi_context = CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_accounts);
+        system_program::transfer(cpi_context, amount)?;
+        msg!("Stake topped up to {} lamports", total);
         Ok(())
     }
 
@@ -183,36 +469,54 @@ pub mod starboard {
         let clock = Clock::get()?;
         let timestamp = clock.slot;
         let round_key = ctx.accounts.round.key().clone();
+        let feed_key = ctx.accounts.feed.key();
         let mut feed = ctx.accounts.feed.load_mut()?;
         let mut round = ctx.accounts.round.load_mut()?;
 
-        // 1 = staking
-        require!(
-            round.current_stage == 1,
-            StarboardError::RoundNotReady
-        );
-        if feed.reporting_round != Some(round_key) {
-            if let Some(_reporting_round) = feed.reporting_round {
-                let old_round = AccountLoader::<Round>::try_from(&ctx.remaining_accounts[0])?;
-                let old_round_data = old_round.load()?;
-                let feed_not_reporting = (old_round_data.reporting_start_timestamp.checked_add(feed.update_interval.into()).unwrap()) < timestamp;
-                require!(feed_not_reporting, StarboardError::ReportingInProgress);
-            }
-        };
+        let old_round = previous_round(&feed, feed_key, StageKind::Reporting, round_key, ctx.remaining_accounts)?;
+        advance(&mut feed, &mut round, round_key, StageKind::Reporting, old_round.as_ref(), timestamp)?;
 
-        feed.reporting_round = Some(round_key);
-        round.current_stage = 2;
-        round.reporting_start_timestamp = timestamp;
-        Ok(())
+        let mut available = ctx.accounts.lease.amount;
+        let bounty = crank_bounty(&feed, &mut round, StageKind::Reporting, &mut available);
+        pay_crank_bounty(
+            &ctx.accounts.lease,
+            &ctx.accounts.keeper_tokens,
+            &ctx.accounts.program_as_signer,
+            &ctx.accounts.token_program,
+            *ctx.bumps.get("program_as_signer").unwrap(),
+            bounty,
+        )
+    }
 
+    pub fn report(ctx: Context<Report>) -> Result<()> {
+        let clock = Clock::get()?;
+        let timestamp = clock.slot;
+        let round_key = ctx.accounts.round.key();
+        let feed = ctx.accounts.feed.load()?;
+        let mut round = ctx.accounts.round.load_mut()?;
+        let escrow = ctx.accounts.escrow.load()?;
+
+        record_report(&feed, &mut round, round_key, &escrow, timestamp)?;
+        let mut report_record = ctx.accounts.report_record.load_init()?;
+        report_record.fill(&round, round_key, &escrow, ctx.accounts.escrow.key(), timestamp);
+        msg!("Reporter registered at {} for round height {}", timestamp, round.round_height);
+        Ok(())
     }
 
-    pub fn report(
-        ctx: Context<Report>,
-        val: u64,
-        confidence_interval: u8
-    ) -> Result<()> {
-        /// TODO
+    /// `report` for feeds in native mode, which keep no report escrow
+    /// token account.
+    pub fn report_native(ctx: Context<ReportNative>) -> Result<()> {
+        let clock = Clock::get()?;
+        let timestamp = clock.slot;
+        let round_key = ctx.accounts.round.key();
+        let feed = ctx.accounts.feed.load()?;
+        let mut round = ctx.accounts.round.load_mut()?;
+        let escrow = ctx.accounts.escrow.load()?;
+
+        record_report(&feed, &mut round, round_key, &escrow, timestamp)?;
+        let mut report_record = ctx.accounts.report_record.load_init()?;
+        report_record.fill(&round, round_key, &escrow, ctx.accounts.escrow.key(), timestamp);
+        msg!("Reporter registered at {} for round height {}", timestamp, round.round_height);
         Ok(())
     }
 
@@ -220,34 +524,92 @@ pub mod starboard {
         let clock = Clock::get()?;
         let timestamp = clock.slot;
         let round_key = ctx.accounts.round.key().clone();
+        let feed_key = ctx.accounts.feed.key();
         let mut feed = ctx.accounts.feed.load_mut()?;
         let mut round = ctx.accounts.round.load_mut()?;
 
-        // 2 = reporting
-        require!(
-            round.current_stage == 2,
-            StarboardError::RoundNotReady
-        );
-        if feed.committing_round != Some(round_key) {
-            if let Some(_committing_round) = feed.committing_round {
-                let old_round = AccountLoader::<Round>::try_from(&ctx.remaining_accounts[0])?;
-                let old_round_data = old_round.load()?;
-                let feed_not_committing = (old_round_data.committing_start_timestamp.checked_add(feed.update_interval.into()).unwrap()) < timestamp;
-                require!(feed_not_committing, StarboardError::ComittingInProgress);
-            }
-        };
-
-        feed.committing_round = Some(round_key);
-        round.current_stage = 3;
-        round.committing_start_timestamp = timestamp;
-        Ok(())
+        let old_round = previous_round(&feed, feed_key, StageKind::Comitting, round_key, ctx.remaining_accounts)?;
+        advance(&mut feed, &mut round, round_key, StageKind::Comitting, old_round.as_ref(), timestamp)?;
 
+        let mut available = ctx.accounts.lease.amount;
+        let bounty = crank_bounty(&feed, &mut round, StageKind::Comitting, &mut available);
+        pay_crank_bounty(
+            &ctx.accounts.lease,
+            &ctx.accounts.keeper_tokens,
+            &ctx.accounts.program_as_signer,
+            &ctx.accounts.token_program,
+            *ctx.bumps.get("program_as_signer").unwrap(),
+            bounty,
+        )
     }
 
     pub fn committ(
         ctx: Context<Comitt>,
         commitment: [u8; 32],
     ) -> Result<()> {
+        let clock = Clock::get()?;
+        let timestamp = clock.slot;
+        let round_key = ctx.accounts.round.key();
+        let feed = ctx.accounts.feed.load()?;
+        let round = ctx.accounts.round.load()?;
+        let mut escrow = ctx.accounts.escrow.load_mut()?;
+
+        round.require_stage(StageKind::Comitting)?;
+        let round_is_committing =
+            feed.committing_round == Some(round_key) &&
+            round.committing_start_timestamp.checked_add(round.update_interval.into()).unwrap() > timestamp;
+        require!(round_is_committing, StarboardError::RoundNotCommitting);
+        require!(
+            escrow.round_height == round.round_height,
+            StarboardError::EscrowRoundMismatch
+        );
+
+        escrow.commitment = commitment;
+        escrow.committed = true;
+        Ok(())
+    }
+
+    pub fn reveal(
+        ctx: Context<Reveal>,
+        value: i64,
+        confidence: u64,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let timestamp = clock.slot;
+        let round_key = ctx.accounts.round.key();
+        let feed = ctx.accounts.feed.load()?;
+        let round = ctx.accounts.round.load()?;
+        let escrow = ctx.accounts.escrow.load()?;
+        let mut report_record = ctx.accounts.report_record.load_mut()?;
+
+        round.require_stage(StageKind::Certifying)?;
+        let round_is_certifying =
+            feed.certifying_round == Some(round_key) &&
+            round.certifying_start_timestamp.checked_add(round.update_interval.into()).unwrap() > timestamp;
+        require!(round_is_certifying, StarboardError::RoundNotCertifying);
+        require!(
+            escrow.round_height == round.round_height &&
+            report_record.round_height == round.round_height,
+            StarboardError::EscrowRoundMismatch
+        );
+        require!(escrow.committed, StarboardError::NoCommitment);
+        require!(!report_record.revealed, StarboardError::AlreadyRevealed);
+
+        let expected = commitment_hash(
+            &ctx.accounts.feed.key(),
+            round.round_height,
+            &ctx.accounts.staker.key(),
+            value,
+            confidence,
+            &salt,
+        );
+        require!(expected == escrow.commitment, StarboardError::CommitmentMismatch);
+
+        report_record.value = value;
+        report_record.confidence = confidence;
+        report_record.revealed = true;
+        msg!("Revealed {} at {} for round height {}", value, timestamp, round.round_height);
         Ok(())
     }
 
@@ -255,30 +617,85 @@ pub mod starboard {
         let clock = Clock::get()?;
         let timestamp = clock.slot;
         let round_key = ctx.accounts.round.key().clone();
+        let feed_key = ctx.accounts.feed.key();
         let mut feed = ctx.accounts.feed.load_mut()?;
         let mut round = ctx.accounts.round.load_mut()?;
 
-        // 3 = committing
-        require!(
-            round.current_stage == 3,
-            StarboardError::RoundNotReady
-        );
-        if feed.certifying_round != Some(round_key) {
-            if let Some(_certifying_round) = feed.certifying_round {
-                let old_round = AccountLoader::<Round>::try_from(&ctx.remaining_accounts[0])?;
-                let old_round_data = old_round.load()?;
-                let feed_not_certifying = (old_round_data.certifying_start_timestamp.checked_add(feed.update_interval.into()).unwrap()) < timestamp;
-                require!(feed_not_certifying, StarboardError::CertifyingInProgress);
-            }
+        let old_round = previous_round(&feed, feed_key, StageKind::Certifying, round_key, ctx.remaining_accounts)?;
+        advance(&mut feed, &mut round, round_key, StageKind::Certifying, old_round.as_ref(), timestamp)?;
+
+        let mut available = ctx.accounts.lease.amount;
+        let bounty = crank_bounty(&feed, &mut round, StageKind::Certifying, &mut available);
+        pay_crank_bounty(
+            &ctx.accounts.lease,
+            &ctx.accounts.keeper_tokens,
+            &ctx.accounts.program_as_signer,
+            &ctx.accounts.token_program,
+            *ctx.bumps.get("program_as_signer").unwrap(),
+            bounty,
+        )
+    }
+
+    pub fn certify(ctx: Context<Certify>) -> Result<()> {
+        let clock = Clock::get()?;
+        let timestamp = clock.slot;
+        let round_key = ctx.accounts.round.key();
+        let feed = ctx.accounts.feed.load()?;
+        let mut round = ctx.accounts.round.load_mut()?;
+        let mut report_record = ctx.accounts.report_record.load_mut()?;
+        let certifier = ctx.accounts.certifier.key();
+
+        let bond = record_certification(&feed, &mut round, round_key, &mut report_record, certifier, timestamp)?;
+        let transfer_accounts = Transfer {
+            from: ctx.accounts.certifier_tokens.to_account_info(),
+            to: ctx.accounts.certifier_escrow.to_account_info(),
+            authority: ctx.accounts.certifier.to_account_info(),
         };
+        let cpi_context = CpiContext::new(ctx.accounts.token_program.to_account_info(), transfer_accounts);
+        transfer(cpi_context, bond)?;
 
-        feed.certifying_round = Some(round_key);
-        round.current_stage = 4;
-        round.certifying_start_timestamp = timestamp;
+        let mut cert_record = ctx.accounts.cert_record.load_init()?;
+        cert_record.fill(
+            &report_record,
+            ctx.accounts.report_record.key(),
+            certifier,
+            ctx.accounts.certifier_escrow.key(),
+            bond,
+            timestamp,
+        );
+        msg!("Certified {} with bond {}", report_record.value, bond);
         Ok(())
     }
 
-    pub fn certify(ctx: Context<Certify>) -> Result<()> {
+    /// `certify` for feeds in native mode: the bond is paid in lamports
+    /// into the CertRecord, which doubles as its escrow.
+    pub fn certify_native(ctx: Context<CertifyNative>) -> Result<()> {
+        let clock = Clock::get()?;
+        let timestamp = clock.slot;
+        let round_key = ctx.accounts.round.key();
+        let feed = ctx.accounts.feed.load()?;
+        let mut round = ctx.accounts.round.load_mut()?;
+        let mut report_record = ctx.accounts.report_record.load_mut()?;
+        let certifier = ctx.accounts.certifier.key();
+
+        let bond = record_certification(&feed, &mut round, round_key, &mut report_record, certifier, timestamp)?;
+        let transfer_accounts = system_program::Transfer {
+            from: ctx.accounts.certifier.to_account_info(),
+            to: ctx.accounts.cert_record.to_account_info(),
+        };
+        let cpi_context = CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_accounts);
+        system_program::transfer(cpi_context, bond)?;
+
+        let mut cert_record = ctx.accounts.cert_record.load_init()?;
+        cert_record.fill(
+            &report_record,
+            ctx.accounts.report_record.key(),
+            certifier,
+            ctx.accounts.cert_record.key(),
+            bond,
+            timestamp,
+        );
+        msg!("Certified {} with bond {} lamports", report_record.value, bond);
         Ok(())
     }
 
@@ -286,48 +703,630 @@ pub mod starboard {
         let clock = Clock::get()?;
         let timestamp = clock.slot;
         let round_key = ctx.accounts.round.key().clone();
+        let feed_key = ctx.accounts.feed.key();
         let mut feed = ctx.accounts.feed.load_mut()?;
         let mut round = ctx.accounts.round.load_mut()?;
 
-        // 4 = certifying
+        let old_round = previous_round(&feed, feed_key, StageKind::Finalizing, round_key, ctx.remaining_accounts)?;
+        advance(&mut feed, &mut round, round_key, StageKind::Finalizing, old_round.as_ref(), timestamp)?;
+
+        let mut available = ctx.accounts.lease.amount;
+        let bounty = crank_bounty(&feed, &mut round, StageKind::Finalizing, &mut available);
+        pay_crank_bounty(
+            &ctx.accounts.lease,
+            &ctx.accounts.keeper_tokens,
+            &ctx.accounts.program_as_signer,
+            &ctx.accounts.token_program,
+            *ctx.bumps.get("program_as_signer").unwrap(),
+            bounty,
+        )
+    }
+
+    /// Permissionless keeper entrypoint. Takes all five Round PDAs of the
+    /// feed and moves every round whose stage has run its update interval
+    /// into the next stage, then starts staking on an idle round if the
+    /// staking slot is free. Rounds in Finalizing are left for `finalize`
+    /// or `fail_round`.
+    pub fn crank(ctx: Context<Crank>) -> Result<()> {
+        let clock = Clock::get()?;
+        let timestamp = clock.slot;
+        let round_keys = [
+            ctx.accounts.round_0.key(),
+            ctx.accounts.round_1.key(),
+            ctx.accounts.round_2.key(),
+            ctx.accounts.round_3.key(),
+            ctx.accounts.round_4.key(),
+        ];
+        let mut feed = ctx.accounts.feed.load_mut()?;
+        let mut rounds = [
+            ctx.accounts.round_0.load_mut()?,
+            ctx.accounts.round_1.load_mut()?,
+            ctx.accounts.round_2.load_mut()?,
+            ctx.accounts.round_3.load_mut()?,
+            ctx.accounts.round_4.load_mut()?,
+        ];
+
+        let mut steps = CRANK_ORDER.to_vec();
+        steps.push((StageKind::Standby, StageKind::Staking));
+        let mut advanced = 0u32;
+        let mut available = ctx.accounts.lease.amount;
+        let mut bounty = 0u64;
+        for (from, to) in steps {
+            let due = (0..rounds.len()).find(|&i| match rounds[i].stage() {
+                Ok(stage) if from == StageKind::Standby => stage.is_idle(),
+                Ok(stage) => stage == from && rounds[i].stage_elapsed(timestamp),
+                Err(_) => false,
+            });
+            let i = match due {
+                Some(i) => i,
+                None => continue,
+            };
+            let old_round = feed.round_for(to)
+                .and_then(|pointer| round_keys.iter().position(|key| *key == pointer))
+                .filter(|&holder| holder != i)
+                .map(|holder| *rounds[holder]);
+            if !slot_is_free(to, old_round.as_ref(), timestamp) {
+                continue;
+            }
+            if to == StageKind::Staking && available < feed.round_cost() {
+                msg!("Lease cannot cover the next round");
+                continue;
+            }
+            advance(&mut feed, &mut rounds[i], round_keys[i], to, old_round.as_ref(), timestamp)?;
+            bounty = bounty.checked_add(crank_bounty(&feed, &mut rounds[i], to, &mut available)).unwrap();
+            advanced += 1;
+        }
+
+        require!(advanced > 0, StarboardError::NothingToCrank);
+        msg!("Cranked {} stages at {}", advanced, timestamp);
+        pay_crank_bounty(
+            &ctx.accounts.lease,
+            &ctx.accounts.keeper_tokens,
+            &ctx.accounts.program_as_signer,
+            &ctx.accounts.token_program,
+            *ctx.bumps.get("program_as_signer").unwrap(),
+            bounty,
+        )
+    }
+
+    /// Aggregates the round's certified ReportRecords, passed in
+    /// `remaining_accounts`, into the feed's latest finalized value.
+    /// Every record of the round must be passed exactly once, writable,
+    /// so reports outside the slashing band and unrevealed reports can be
+    /// flagged for `slash_reporter`; the feed's `max_reporters` keeps them
+    /// within one transaction. Revealed reports nobody certified are left
+    /// out of the aggregate and earn nothing, but keep their stake.
+    pub fn finalize(ctx: Context<Finalize>) -> Result<()> {
+        let clock = Clock::get()?;
+        let timestamp = clock.slot;
+        let round_key = ctx.accounts.round.key();
+        let mut feed = ctx.accounts.feed.load_mut()?;
+        let mut round = ctx.accounts.round.load_mut()?;
+
+        round.require_stage(StageKind::Finalizing)?;
+        require!(
+            feed.finalizing_round == Some(round_key),
+            StarboardError::RoundNotFinalizing
+        );
+        require!(
+            ctx.remaining_accounts.len() == round.num_reports as usize,
+            StarboardError::MissingReports
+        );
+        require!(round.num_certified > 0, StarboardError::NoCertifiedReports);
+        require!(
+            round.num_certified >= round.min_reporters,
+            StarboardError::QuorumNotMet
+        );
+
+        let mut seen: Vec<Pubkey> = Vec::with_capacity(ctx.remaining_accounts.len());
+        let mut records: Vec<AccountLoader<ReportRecord>> = Vec::with_capacity(ctx.remaining_accounts.len());
+        let mut samples: Vec<Sample> = Vec::with_capacity(ctx.remaining_accounts.len());
+        let mut values: Vec<i64> = Vec::with_capacity(ctx.remaining_accounts.len());
+        let mut num_unrevealed = 0u32;
+        let mut num_uncertified = 0u32;
+        for account in ctx.remaining_accounts.iter() {
+            require!(!seen.contains(account.key), StarboardError::DuplicateReport);
+            seen.push(*account.key);
+            let loader = AccountLoader::<ReportRecord>::try_from(account)?;
+            let mut report_record = loader.load_mut()?;
+            require!(
+                report_record.round == round_key &&
+                report_record.round_height == round.round_height,
+                StarboardError::EscrowRoundMismatch
+            );
+            // reporters who never revealed forfeit their stake like an
+            // out-of-band report
+            if !report_record.revealed {
+                report_record.finalized = true;
+                report_record.slashed = true;
+                num_unrevealed = num_unrevealed.checked_add(1).unwrap();
+                continue;
+            }
+            if report_record.num_certifications == 0 {
+                report_record.finalized = true;
+                num_uncertified = num_uncertified.checked_add(1).unwrap();
+                continue;
+            }
+            samples.push(Sample {
+                value: report_record.value,
+                weight: report_record.stake,
+                confidence: report_record.confidence,
+            });
+            values.push(report_record.value);
+            drop(report_record);
+            records.push(loader);
+        }
         require!(
-            round.current_stage == 4,
-            StarboardError::RoundNotReady
+            records.len() == round.num_certified as usize,
+            StarboardError::MissingReports
         );
-        if feed.finalizing_round != Some(round_key) {
-            if let Some(_finalizing_round) = feed.finalizing_round {
-                let old_round = AccountLoader::<Round>::try_from(&ctx.remaining_accounts[0])?;
-                let old_round_data = old_round.load()?;
-                let feed_not_finalizing = (old_round_data.finalizing_start_timestamp.checked_add(feed.update_interval.into()).unwrap()) < timestamp;
-                require!(feed_not_finalizing, StarboardError::FinalizingInProgress);
+
+        let value = aggregate(AggregationKind::try_from(round.aggregation)?, &mut samples, round.trim_bps);
+        let band = slashing_band(value, &values, round.slash_band_bps, round.slash_mad_multiple);
+        let mut num_slashed = 0u32;
+        let mut honest_weight = 0u64;
+        for loader in records.iter() {
+            let mut report_record = loader.load_mut()?;
+            report_record.finalized = true;
+            let weight = report_record.stake.checked_add(report_record.cert_bond_total).unwrap();
+            if report_record.value.abs_diff(value) > band {
+                report_record.slashed = true;
+                num_slashed = num_slashed.checked_add(1).unwrap();
+            } else {
+                honest_weight = honest_weight.checked_add(weight).unwrap();
+            }
+        }
+        msg!(
+            "Slashing band {}, {} reports slashed, {} unrevealed, {} uncertified",
+            band,
+            num_slashed,
+            num_unrevealed,
+            num_uncertified
+        );
+
+        // rewards come out of the lease plus whatever the vault holds
+        // beyond the rewards already owed, i.e. stake and bonds that
+        // earlier slashes have collected, and are split pro-rata by stake
+        // and bond between the honest reporters and their certifiers.
+        // Stake flagged by this round only reaches the vault once it is
+        // slashed, so it pays out with a later round.
+        let collected = ctx.accounts.vault.amount.saturating_sub(feed.rewards_owed);
+        let fees = if honest_weight > 0 {
+            feed.reward_per_round.min(ctx.accounts.lease.amount)
+        } else {
+            0
+        };
+        // the protocol takes its cut of the lease-funded part to the treasury
+        let protocol_fee = pro_rata(
+            fees,
+            ctx.accounts.starboard.load()?.protocol_fee_bps.into(),
+            BPS_DENOMINATOR as u64,
+        );
+        let reporter_fees = fees.checked_sub(protocol_fee).unwrap();
+        let bump = *ctx.bumps.get("program_as_signer").unwrap();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"program", b"signer", &[bump]]];
+        if protocol_fee > 0 {
+            let transfer_accounts = Transfer {
+                from: ctx.accounts.lease.to_account_info(),
+                to: ctx.accounts.treasury.to_account_info(),
+                authority: ctx.accounts.program_as_signer.to_account_info(),
+            };
+            let cpi_context = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), transfer_accounts, signer_seeds);
+            transfer(cpi_context, protocol_fee)?;
+        }
+        if reporter_fees > 0 {
+            let transfer_accounts = Transfer {
+                from: ctx.accounts.lease.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.program_as_signer.to_account_info(),
+            };
+            let cpi_context = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), transfer_accounts, signer_seeds);
+            transfer(cpi_context, reporter_fees)?;
+        }
+        let pool = reporter_fees.checked_add(collected).unwrap();
+        let mut allocated = 0u64;
+        if honest_weight > 0 {
+            for loader in records.iter() {
+                let mut report_record = loader.load_mut()?;
+                if report_record.slashed {
+                    continue;
+                }
+                report_record.reporter_reward = pro_rata(pool, report_record.stake, honest_weight);
+                report_record.cert_reward = pro_rata(pool, report_record.cert_bond_total, honest_weight);
+                allocated = allocated
+                    .checked_add(report_record.reporter_reward).unwrap()
+                    .checked_add(report_record.cert_reward).unwrap();
             }
+        }
+        feed.rewards_owed = feed.rewards_owed.checked_add(allocated).unwrap();
+        msg!("Reward pool {}, {} allocated, protocol fee {}", pool, allocated, protocol_fee);
+
+        feed.latest_finalized_value = value;
+        feed.latest_finalized_confidence = output_confidence(&samples, value);
+        feed.latest_finalized_timestamp = timestamp;
+        feed.latest_finalized_round_height = round.round_height;
+        feed.stale = false;
+        round.transition(StageKind::Finalized)?;
+        round.recycle();
+        msg!("Finalized {} at {} for round height {}", value, timestamp, round.round_height);
+        Ok(())
+    }
+
+    /// Returns a staker's escrowed tokens once the round it staked on has
+    /// finalized, and closes the escrow accounts.
+    pub fn withdraw_stake(ctx: Context<WithdrawStake>) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+        let round = ctx.accounts.round.load()?;
+        require_withdrawable(&escrow, &round, &ctx.accounts.report_record.to_account_info())?;
+
+        let amount = ctx.accounts.escrow_token.amount;
+        let bump = *ctx.bumps.get("program_as_signer").unwrap();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"program", b"signer", &[bump]]];
+        let transfer_accounts = Transfer {
+            from: ctx.accounts.escrow_token.to_account_info(),
+            to: ctx.accounts.staker_tokens.to_account_info(),
+            authority: ctx.accounts.program_as_signer.to_account_info(),
         };
+        let cpi_context = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), transfer_accounts, signer_seeds);
+        transfer(cpi_context, amount)?;
 
-        feed.finalizing_round = Some(round_key);
-        round.current_stage = 4;
-        round.finalizing_start_timestamp = timestamp;
+        let close_accounts = CloseAccount {
+            account: ctx.accounts.escrow_token.to_account_info(),
+            destination: ctx.accounts.staker.to_account_info(),
+            authority: ctx.accounts.program_as_signer.to_account_info(),
+        };
+        let cpi_context = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), close_accounts, signer_seeds);
+        close_account(cpi_context)?;
+        msg!("Withdrew {} for round height {}", amount, escrow.round_height);
         Ok(())
     }
 
-    pub fn finalize(ctx: Context<Finalize>) -> Result<()> {
+    /// `withdraw_stake` for feeds in native mode. Closing the escrow
+    /// returns the staked lamports along with its rent.
+    pub fn withdraw_stake_native(ctx: Context<WithdrawStakeNative>) -> Result<()> {
+        let escrow = ctx.accounts.escrow.load()?;
+        let round = ctx.accounts.round.load()?;
+        require_withdrawable(&escrow, &round, &ctx.accounts.report_record.to_account_info())?;
+        msg!("Withdrew {} lamports for round height {}", escrow.amount, escrow.round_height);
+        Ok(())
+    }
+
+    /// Pays an honest reporter its share of a finalized round's rewards
+    /// out of the feed vault.
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        let mut feed = ctx.accounts.feed.load_mut()?;
+        let mut report_record = ctx.accounts.report_record.load_mut()?;
+        let amount = claim_reporter_reward(&mut feed, &mut report_record)?;
+
+        let bump = *ctx.bumps.get("program_as_signer").unwrap();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"program", b"signer", &[bump]]];
+        let transfer_accounts = Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.reporter_tokens.to_account_info(),
+            authority: ctx.accounts.program_as_signer.to_account_info(),
+        };
+        let cpi_context = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), transfer_accounts, signer_seeds);
+        transfer(cpi_context, amount)?;
+        msg!("Paid {} to reporter {}", amount, ctx.accounts.reporter.key());
+        Ok(())
+    }
+
+    /// `claim_rewards` for feeds in native mode. The reward is paid out
+    /// of the vault as lamports.
+    pub fn claim_rewards_native(ctx: Context<ClaimRewardsNative>) -> Result<()> {
+        let mut feed = ctx.accounts.feed.load_mut()?;
+        let mut report_record = ctx.accounts.report_record.load_mut()?;
+        let amount = claim_reporter_reward(&mut feed, &mut report_record)?;
+        pay_out_native(
+            &ctx.accounts.vault,
+            &ctx.accounts.payout,
+            &ctx.accounts.reporter.to_account_info(),
+            &ctx.accounts.program_as_signer.to_account_info(),
+            &ctx.accounts.token_program,
+            *ctx.bumps.get("program_as_signer").unwrap(),
+            amount,
+        )?;
+        msg!("Paid {} lamports to reporter {}", amount, ctx.accounts.reporter.key());
+        Ok(())
+    }
+
+    /// Returns a certifier's bond behind an honest report together with
+    /// its share of the round's rewards, and closes its CertRecord.
+    /// Bonds behind reports of a failed round are returned without reward.
+    pub fn claim_cert_rewards(ctx: Context<ClaimCertRewards>) -> Result<()> {
+        let mut feed = ctx.accounts.feed.load_mut()?;
+        let round = ctx.accounts.round.load()?;
+        let mut report_record = ctx.accounts.report_record.load_mut()?;
+        let cert_record = ctx.accounts.cert_record.load()?;
+        let reward = settle_certification(&mut feed, &round, &mut report_record, &cert_record)?;
+
+        let bump = *ctx.bumps.get("program_as_signer").unwrap();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"program", b"signer", &[bump]]];
+        let transfer_accounts = Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.certifier_tokens.to_account_info(),
+            authority: ctx.accounts.program_as_signer.to_account_info(),
+        };
+        let cpi_context = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), transfer_accounts, signer_seeds);
+        transfer(cpi_context, reward)?;
+
+        let bond = ctx.accounts.certifier_escrow.amount;
+        let transfer_accounts = Transfer {
+            from: ctx.accounts.certifier_escrow.to_account_info(),
+            to: ctx.accounts.certifier_tokens.to_account_info(),
+            authority: ctx.accounts.program_as_signer.to_account_info(),
+        };
+        let cpi_context = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), transfer_accounts, signer_seeds);
+        transfer(cpi_context, bond)?;
+
+        let close_accounts = CloseAccount {
+            account: ctx.accounts.certifier_escrow.to_account_info(),
+            destination: ctx.accounts.certifier.to_account_info(),
+            authority: ctx.accounts.program_as_signer.to_account_info(),
+        };
+        let cpi_context = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), close_accounts, signer_seeds);
+        close_account(cpi_context)?;
+        msg!("Paid {} and returned bond {} to certifier {}", reward, bond, ctx.accounts.certifier.key());
+        Ok(())
+    }
+
+    /// `claim_cert_rewards` for feeds in native mode. The reward is paid
+    /// out of the vault as lamports, the bond lamports come back when the
+    /// CertRecord is closed.
+    pub fn claim_cert_rewards_native(ctx: Context<ClaimCertRewardsNative>) -> Result<()> {
+        let mut feed = ctx.accounts.feed.load_mut()?;
+        let round = ctx.accounts.round.load()?;
+        let mut report_record = ctx.accounts.report_record.load_mut()?;
+        let cert_record = ctx.accounts.cert_record.load()?;
+        let reward = settle_certification(&mut feed, &round, &mut report_record, &cert_record)?;
+        pay_out_native(
+            &ctx.accounts.vault,
+            &ctx.accounts.payout,
+            &ctx.accounts.certifier.to_account_info(),
+            &ctx.accounts.program_as_signer.to_account_info(),
+            &ctx.accounts.token_program,
+            *ctx.bumps.get("program_as_signer").unwrap(),
+            reward,
+        )?;
+        msg!("Paid {} and returned bond {} lamports to certifier {}", reward, cert_record.bond, ctx.accounts.certifier.key());
+        Ok(())
+    }
+
+    /// Returns the feed's latest value to the calling program through
+    /// return data. Fails if the feed or protocol is paused, the last
+    /// round failed, or the value is more than `max_age_slots` old.
+    pub fn read_feed(ctx: Context<ReadFeed>, max_age_slots: u64) -> Result<FeedReading> {
+        let feed = ctx.accounts.feed.load()?;
+        feed.reading(Clock::get()?.slot, max_age_slots)
+    }
+
+    /// Fails a round that missed its quorum or a stage deadline, leaving
+    /// it idle in the `Failed` stage. The feed keeps its previous value, flagged
+    /// as stale unless a later height has finalized, and every stake and
+    /// bond of the round becomes refundable.
+    /// Permissionless.
+    pub fn fail_round(ctx: Context<FailRound>) -> Result<()> {
+        let clock = Clock::get()?;
+        let timestamp = clock.slot;
+        let mut feed = ctx.accounts.feed.load_mut()?;
+        let mut round = ctx.accounts.round.load_mut()?;
+
+        let stage = round.stage()?;
+        let deadline = round.stage_start_timestamp()
+            .checked_add(
+                u64::from(round.update_interval).checked_mul(STAGE_DEADLINE_INTERVALS).unwrap()
+            ).unwrap();
+        let missed_deadline = deadline < timestamp;
+        let missed_quorum = match stage {
+            StageKind::Reporting | StageKind::Comitting | StageKind::Certifying =>
+                round.num_stakers < round.min_reporters,
+            StageKind::Finalizing =>
+                round.num_stakers < round.min_reporters ||
+                round.num_certified < round.min_reporters,
+            _ => false,
+        };
+        require!(missed_deadline || missed_quorum, StarboardError::RoundNotFailable);
+        round.transition(StageKind::Failed)?;
+
+        // a later height may already have finalized past this one, whose
+        // value is then still current
+        if round.round_height > feed.latest_finalized_round_height {
+            feed.stale = true;
+        }
+        round.recycle();
+        msg!(
+            "Round height {} failed (missed deadline: {}, missed quorum: {})",
+            round.round_height,
+            missed_deadline,
+            missed_quorum
+        );
+        Ok(())
+    }
+
+    /// Closes a ReportRecord once its round has settled, so the reporter
+    /// can report again when the recycled round comes back around. Every
+    /// certification must be settled, rewards claimed and slashed stake
+    /// collected first.
+    pub fn close_report(ctx: Context<CloseReport>) -> Result<()> {
+        let round = ctx.accounts.round.load()?;
+        let report_record = ctx.accounts.report_record.load()?;
+        require_closable(&round, &report_record, &ctx.accounts.escrow)?;
+
+        let bump = *ctx.bumps.get("program_as_signer").unwrap();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"program", b"signer", &[bump]]];
+        let amount = ctx.accounts.report_escrow.amount;
+        if amount > 0 {
+            let transfer_accounts = Transfer {
+                from: ctx.accounts.report_escrow.to_account_info(),
+                to: ctx.accounts.reporter_tokens.to_account_info(),
+                authority: ctx.accounts.program_as_signer.to_account_info(),
+            };
+            let cpi_context = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), transfer_accounts, signer_seeds);
+            transfer(cpi_context, amount)?;
+        }
+        let close_accounts = CloseAccount {
+            account: ctx.accounts.report_escrow.to_account_info(),
+            destination: ctx.accounts.reporter.to_account_info(),
+            authority: ctx.accounts.program_as_signer.to_account_info(),
+        };
+        let cpi_context = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), close_accounts, signer_seeds);
+        close_account(cpi_context)?;
+        Ok(())
+    }
+
+    /// `close_report` for feeds in native mode, which keep no report
+    /// escrow token account.
+    pub fn close_report_native(ctx: Context<CloseReportNative>) -> Result<()> {
+        let round = ctx.accounts.round.load()?;
+        let report_record = ctx.accounts.report_record.load()?;
+        require_closable(&round, &report_record, &ctx.accounts.escrow)
+    }
+
+    /// Moves the stake of a reporter flagged by `finalize` into the
+    /// feed vault and closes its escrow. Permissionless.
+    pub fn slash_reporter(ctx: Context<SlashReporter>) -> Result<()> {
+        let amount = ctx.accounts.escrow_token.amount;
+        let bump = *ctx.bumps.get("program_as_signer").unwrap();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"program", b"signer", &[bump]]];
+
+        let transfer_accounts = Transfer {
+            from: ctx.accounts.escrow_token.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.program_as_signer.to_account_info(),
+        };
+        let cpi_context = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), transfer_accounts, signer_seeds);
+        transfer(cpi_context, amount)?;
+
+        let close_accounts = CloseAccount {
+            account: ctx.accounts.escrow_token.to_account_info(),
+            destination: ctx.accounts.staker.to_account_info(),
+            authority: ctx.accounts.program_as_signer.to_account_info(),
+        };
+        let cpi_context = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), close_accounts, signer_seeds);
+        close_account(cpi_context)?;
+        msg!("Slashed {} from reporter {}", amount, ctx.accounts.staker.key());
+        Ok(())
+    }
+
+    /// `slash_reporter` for feeds in native mode. The staked lamports are
+    /// wrapped into the vault, the escrow's rent goes back to the staker.
+    pub fn slash_reporter_native(ctx: Context<SlashReporterNative>) -> Result<()> {
+        let amount = ctx.accounts.escrow.load()?.amount;
+        sweep_into_vault(
+            &ctx.accounts.escrow.to_account_info(),
+            &ctx.accounts.vault,
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+        msg!("Slashed {} lamports from reporter {}", amount, ctx.accounts.staker.key());
+        Ok(())
+    }
+
+    /// Moves the bond of a certifier who backed a slashed report into
+    /// the feed vault and closes its CertRecord. Permissionless.
+    pub fn slash_certifier(ctx: Context<SlashCertifier>) -> Result<()> {
+        let mut report_record = ctx.accounts.report_record.load_mut()?;
+        report_record.certs_settled = report_record.certs_settled.checked_add(1).unwrap();
+        let amount = ctx.accounts.certifier_escrow.amount;
+        let bump = *ctx.bumps.get("program_as_signer").unwrap();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"program", b"signer", &[bump]]];
+
+        let transfer_accounts = Transfer {
+            from: ctx.accounts.certifier_escrow.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.program_as_signer.to_account_info(),
+        };
+        let cpi_context = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), transfer_accounts, signer_seeds);
+        transfer(cpi_context, amount)?;
+
+        let close_accounts = CloseAccount {
+            account: ctx.accounts.certifier_escrow.to_account_info(),
+            destination: ctx.accounts.certifier.to_account_info(),
+            authority: ctx.accounts.program_as_signer.to_account_info(),
+        };
+        let cpi_context = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), close_accounts, signer_seeds);
+        close_account(cpi_context)?;
+        msg!("Slashed {} from certifier {}", amount, ctx.accounts.certifier.key());
+        Ok(())
+    }
+
+    /// `slash_certifier` for feeds in native mode. The bond lamports are
+    /// wrapped into the vault, the CertRecord's rent goes back to the
+    /// certifier.
+    pub fn slash_certifier_native(ctx: Context<SlashCertifierNative>) -> Result<()> {
+        let mut report_record = ctx.accounts.report_record.load_mut()?;
+        report_record.certs_settled = report_record.certs_settled.checked_add(1).unwrap();
+        let amount = ctx.accounts.cert_record.load()?.bond;
+        sweep_into_vault(
+            &ctx.accounts.cert_record.to_account_info(),
+            &ctx.accounts.vault,
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+        msg!("Slashed {} lamports from certifier {}", amount, ctx.accounts.certifier.key());
         Ok(())
     }
 
 }
 
-#[derive(Copy, Clone)]
-#[repr(u8)]
-pub enum StageKind {
-    Standby,
-    Staking,
-    Reporting,
-    Comitting,
-    Certifying,
-    Finalizing
+/// `amount * part / total`, rounded down.
+pub fn pro_rata(amount: u64, part: u64, total: u64) -> u64 {
+    if total == 0 {
+        return 0;
+    }
+    ((amount as u128) * (part as u128) / (total as u128)) as u64
 }
 
+/// Largest deviation from the aggregate a report may have without being
+/// slashed: the wider of `band_bps` of the aggregate and `mad_multiple`
+/// times the median absolute deviation of the reports.
+pub fn slashing_band(aggregate: i64, values: &[i64], band_bps: u32, mad_multiple: u32) -> u64 {
+    let pct_band = (aggregate.unsigned_abs() as u128) * (band_bps as u128) / BPS_DENOMINATOR;
+    let mut deviations: Vec<u64> = values.iter().map(|v| v.abs_diff(aggregate)).collect();
+    let mad_band = (median(&mut deviations) as u128) * (mad_multiple as u128);
+    pct_band.max(mad_band).min(u64::MAX as u128) as u64
+}
+
+/// Hash a reporter commits to during the committing stage.
+/// Domain-separated by feed, round height and staker so a commitment
+/// can't be replayed on another feed, round or by another staker.
+pub fn commitment_hash(
+    feed: &Pubkey,
+    round_height: u64,
+    staker: &Pubkey,
+    value: i64,
+    confidence: u64,
+    salt: &[u8; 32],
+) -> [u8; 32] {
+    hashv(&[
+        COMMITMENT_DOMAIN,
+        feed.as_ref(),
+        &round_height.to_le_bytes(),
+        staker.as_ref(),
+        &value.to_le_bytes(),
+        &confidence.to_le_bytes(),
+        salt,
+    ]).to_bytes()
+}
+
+/// Protocol-wide config, a singleton PDA at `[STARBOARD_SEED]`.
 #[account(zero_copy)]
-pub struct Starboard {}
+pub struct Starboard {
+    pub admin: Pubkey,
+    // owner of the token accounts, one per stake mint, receiving the
+    // protocol fee
+    pub treasury: Pubkey,
+    // stake mint clients use for new feeds unless they pick their own
+    pub default_stake_mint: Pubkey,
+    // cut of each round's lease-funded rewards sent to the treasury
+    pub protocol_fee_bps: u32,
+    // used by `init_feed` when a feed does not set its own
+    pub default_slash_band_bps: u32,
+    pub default_slash_mad_multiple: u32,
+    // halts every feed
+    pub paused: bool,
+    pub bump: u8,
+}
 
 #[account(zero_copy)]
 pub struct Round {
@@ -343,20 +1342,81 @@ pub struct Round {
     pub bump: u8,
     pub num: u8,
     pub round_height: u64,
+    // number of ReportRecords with at least one certification
+    pub num_certified: u32,
+    // number of ReportRecords registered at round_height, every one of
+    // which `finalize` must see
+    pub num_reports: u32,
+    // bit per StageKind whose crank bounty was paid at round_height
+    pub bounties_paid: u8,
+    // feed config taken when the round started staking, so changes made
+    // with `set_feed_config` never reach a round already in flight
+    pub aggregation: u8,
+    pub update_interval: u32,
+    pub min_reporters: u32,
+    pub min_stake: u64,
+    pub slash_band_bps: u32,
+    pub slash_mad_multiple: u32,
+    pub max_stake: u64,
+    pub trim_bps: u32,
+    pub max_reporters: u32,
+}
+
+impl Round {
+    /// Slot at which the round entered its current stage.
+    pub fn stage_start_timestamp(&self) -> u64 {
+        StageKind::try_from(self.current_stage)
+            .map(|stage| self.start_timestamp(stage))
+            .unwrap_or(0)
+    }
+
+    /// Clears the per-round state once a height has finished and the round
+    /// is idle again. `round_height` is kept until the next `start_staking`
+    /// so escrows of the finished height can still settle.
+    pub fn recycle(&mut self) {
+        self.num_stakers = 0;
+        self.num_certified = 0;
+        self.num_reports = 0;
+        self.staking_start_timestamp = 0;
+        self.reporting_start_timestamp = 0;
+        self.committing_start_timestamp = 0;
+        self.certifying_start_timestamp = 0;
+        self.finalizing_start_timestamp = 0;
+    }
+
+    /// Whether the round has finished with `round_height`, either by
+    /// going idle or by moving on to a later height.
+    pub fn is_settled(&self, round_height: u64) -> bool {
+        let current_height = self.round_height;
+        let idle = StageKind::try_from(self.current_stage)
+            .map(StageKind::is_idle)
+            .unwrap_or(false);
+        current_height > round_height ||
+            (current_height == round_height && idle)
+    }
 }
 
 #[account(zero_copy)]
 pub struct Feed {
+    // FEED_LAYOUT_VERSION the account was created with
+    pub version: u8,
     // string describing the feed, e.g "SOL/USD Spot Price"
     pub description: [u8; 32],
-    // TokenAccount for feed's STARB holdings
-    /// TODO
-    //pub lease: Pubkey,
-    pub latest_finalized_value: u64,
+    // TokenAccount consumers fund to pay rewards and crank bounties
+    pub lease: Pubkey,
+    // may withdraw unused funds from the lease
+    pub lease_authority: Pubkey,
+    // price as `latest_finalized_value * 10^exponent`, Pyth-style
+    pub latest_finalized_value: i64,
+    pub latest_finalized_confidence: u64,
+    // slot of the last finalize, despite the name
     pub latest_finalized_timestamp: u64,
+    pub latest_finalized_round_height: u64,
 
     // how many slots the rounds last
     pub update_interval: u32,
+    // decimal exponent of every value reported to the feed, fixed at init_feed
+    pub exponent: i32,
 
     // Pubkeys of the Round PDAs for each of the five pipelined-stages
     pub staking_round: Option<Pubkey>,
@@ -367,18 +1427,63 @@ pub struct Feed {
     pub bump: u8,
     pub started: bool,
     pub height: u64,
-    // in lamports
+    // smallest total a staker may escrow on a round
     pub min_stake: u64,
+    // largest total a single staker may escrow on a round, 0 for no cap
+    pub max_stake: u64,
+    // bond a certifier posts behind each ReportRecord it certifies
+    pub cert_bond: u64,
+    // TokenAccount collecting slashed stake
+    pub vault: Pubkey,
+    // SPL mint of stakes, bonds, rewards and the lease; every token
+    // account of the feed is derived for it
+    pub stake_mint: Pubkey,
+    // reports further than the wider of these bands from the aggregate are slashed
+    pub slash_band_bps: u32,
+    pub slash_mad_multiple: u32,
+    // paid from the vault to each finalized round on top of slashed stake
+    pub reward_per_round: u64,
+    // rewards allocated by `finalize` but not yet claimed
+    pub rewards_owed: u64,
+    // certified reports a round needs to finalize
+    pub min_reporters: u32,
+    // set when the last round failed and the value was not updated
+    pub stale: bool,
+    // set by `pause_feed`; the feed rejects every stage transition and
+    // every write except withdrawals, and `latest_finalized_value` should
+    // not be trusted. Also check `Starboard.paused` for a protocol pause.
+    pub paused: bool,
+    // stakes and certifier bonds are held as lamports in the Escrow and
+    // CertRecord PDAs instead of SPL token accounts; the stake mint is
+    // then wrapped SOL, which the vault and lease keep using
+    pub native_stake: bool,
+    // paid from the lease to whoever first moves a round into each stage
+    pub crank_bounty: u64,
+    // may change the feed's config with `set_feed_config`
+    pub authority: Pubkey,
+    // AggregationKind `finalize` uses, and for TrimmedMean the share of
+    // reports dropped from each end
+    pub aggregation: u8,
+    pub trim_bps: u32,
+    // reports a round takes at most, so `finalize` can see all of them
+    pub max_reporters: u32,
 }
 
 #[account(zero_copy)]
 pub struct Escrow {
     pub round_height: u64,
+    // where the stake is held: the escrow token account, or the Escrow
+    // itself for feeds in native mode
     pub token_account: Pubkey,
+    pub mint: Pubkey,
     pub staker: Pubkey,
     pub feed: Pubkey,
     pub bump: u8,
     pub timestamp: u64,
+    // hash of the sealed report, see `commitment_hash`
+    pub commitment: [u8; 32],
+    pub committed: bool,
+    pub amount: u64,
 }
 
 #[account(zero_copy)]
@@ -389,12 +1494,22 @@ pub struct ReportRecord {
     pub feed: Pubkey,
     pub reporter: Pubkey,
     pub escrow: Pubkey,
-    // note: fix the types here
-    /* confidence interval could be a percentage
-     * or we could do it the Pyth way.
-     */
-    pub value: u64,
-    pub confidence_interval: u8,
+    // price and confidence as multiples of 10^feed.exponent, Pyth-style
+    pub value: i64,
+    pub confidence: u64,
+    pub revealed: bool,
+    pub num_certifications: u32,
+    pub finalized: bool,
+    pub slashed: bool,
+    // reporter's stake and the sum of the certifiers' bonds
+    pub stake: u64,
+    pub cert_bond_total: u64,
+    // allocated by `finalize`, the certifiers split theirs by bond
+    pub reporter_reward: u64,
+    pub cert_reward: u64,
+    pub reward_claimed: bool,
+    // certifications whose bond was returned or slashed
+    pub certs_settled: u32,
 }
 
 #[account(zero_copy)]
@@ -405,12 +1520,12 @@ pub struct CertRecord {
     pub feed: Pubkey,
     pub reporter: Pubkey,
     pub escrow: Pubkey,
-    // note: fix the types here
-    /* confidence interval could be a percentage
-     * or we could do it the Pyth way.
-     */
-    pub value: u64,
-    pub confidence_interval: u8,
+    // the certified report's value and confidence
+    pub value: i64,
+    pub confidence: u64,
+    pub certifier: Pubkey,
+    pub report_record: Pubkey,
+    pub bond: u64,
 }
 
 #[error_code]
@@ -419,6 +1534,12 @@ pub enum StarboardError {
     NoRoundsError,
     #[msg("Round not ready to change state")]
     RoundNotReady,
+    #[msg("Round is not in the expected stage")]
+    WrongStage,
+    #[msg("Round cannot move to this stage from its current stage")]
+    InvalidStageTransition,
+    #[msg("Round stage is not a valid StageKind")]
+    InvalidStage,
     #[msg("Staking for this feed already in progress")]
     StakingInProgress,
     #[msg("Reporting for this feed already in progress")]
@@ -431,4 +1552,98 @@ pub enum StarboardError {
     FinalizingInProgress,
     #[msg("Cannot stake on this round")]
     RoundNotStaking,
+    #[msg("Cannot report on this round")]
+    RoundNotReporting,
+    #[msg("Escrow was not staked for this round's height")]
+    EscrowRoundMismatch,
+    #[msg("Cannot commit on this round")]
+    RoundNotCommitting,
+    #[msg("Cannot reveal or certify on this round")]
+    RoundNotCertifying,
+    #[msg("No commitment made for this escrow")]
+    NoCommitment,
+    #[msg("Report already revealed")]
+    AlreadyRevealed,
+    #[msg("Revealed value does not match the commitment")]
+    CommitmentMismatch,
+    #[msg("Report has not been revealed")]
+    ReportNotRevealed,
+    #[msg("Reporters cannot certify their own report")]
+    SelfCertification,
+    #[msg("Round is not finalizing")]
+    RoundNotFinalizing,
+    #[msg("Every report of the round must be passed to finalize")]
+    MissingReports,
+    #[msg("No certified reports for this round")]
+    NoCertifiedReports,
+    #[msg("Report passed more than once")]
+    DuplicateReport,
+    #[msg("Report has not been certified")]
+    ReportNotCertified,
+    #[msg("Report was not slashed")]
+    NotSlashed,
+    #[msg("No rewards for this report")]
+    NoRewards,
+    #[msg("Rewards already claimed")]
+    RewardsAlreadyClaimed,
+    #[msg("Round for this escrow has not finalized")]
+    RoundNotSettled,
+    #[msg("Stake was slashed")]
+    StakeSlashed,
+    #[msg("Not enough certified reports to finalize")]
+    QuorumNotMet,
+    #[msg("Round has neither missed its quorum nor a deadline")]
+    RoundNotFailable,
+    #[msg("Certifications of this report have not all been settled")]
+    CertificationsNotSettled,
+    #[msg("Rewards must be claimed first")]
+    RewardsNotClaimed,
+    #[msg("Slashed stake has not been collected")]
+    SlashNotCollected,
+    #[msg("No stage of the pipeline is due to advance")]
+    NothingToCrank,
+    #[msg("The round currently holding this stage must be passed in remaining_accounts")]
+    MissingPreviousRound,
+    #[msg("Previous round does not match the feed's stage pointer")]
+    PreviousRoundMismatch,
+    #[msg("Previous round belongs to a different feed")]
+    PreviousRoundFeedMismatch,
+    #[msg("Lease cannot cover the next round")]
+    LeaseUnderfunded,
+    #[msg("Lease holds less than the requested amount")]
+    InsufficientLease,
+    #[msg("Feed config is invalid: update interval, min stake and min reporters must be non-zero, max stake 0 or at least min stake, and the trim under 5000 bps")]
+    InvalidFeedConfig,
+    #[msg("Protocol fee cannot exceed 10000 bps")]
+    InvalidProtocolConfig,
+    #[msg("The protocol is paused")]
+    ProtocolPaused,
+    #[msg("The feed is paused")]
+    FeedPaused,
+    #[msg("Only the feed authority or the protocol admin can do this")]
+    NotFeedAuthorityOrAdmin,
+    #[msg("Native staking needs the wrapped SOL stake mint")]
+    NativeStakeNeedsNativeMint,
+    #[msg("Instruction does not match the feed's staking mode")]
+    WrongStakeMode,
+    #[msg("Treasury must be a token account of the feed's stake mint owned by the protocol treasury")]
+    InvalidTreasury,
+    #[msg("Stake is below the round's minimum")]
+    StakeBelowMinimum,
+    #[msg("Stake is above the round's per-staker cap")]
+    StakeAboveCap,
+    #[msg("Unknown aggregation kind")]
+    InvalidAggregation,
+    #[msg("Only the program's upgrade authority can initialize the protocol")]
+    NotUpgradeAuthority,
+    #[msg("The feed has not finalized a value yet")]
+    NoFinalizedValue,
+    #[msg("The feed's last round failed, its value was not updated")]
+    LastRoundFailed,
+    #[msg("Feed value is older than max_age_slots")]
+    ValueTooOld,
+    #[msg("Stake mint is not the protocol default and the feed did not override it")]
+    StakeMintNotDefault,
+    #[msg("The round already has the feed's maximum number of reports")]
+    RoundFull,
 }
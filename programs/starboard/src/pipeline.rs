@@ -0,0 +1,215 @@
+use crate::*;
+use anchor_spl::token::{TokenAccount, Token, Transfer, transfer};
+
+/// Pipeline stages a round is pushed through by `start_*` and `crank`,
+/// paired with the stage it has to be in beforehand. Listed from the
+/// back of the pipeline so a crank frees each slot before filling it.
+pub const CRANK_ORDER: [(StageKind, StageKind); 4] = [
+    (StageKind::Certifying, StageKind::Finalizing),
+    (StageKind::Comitting, StageKind::Certifying),
+    (StageKind::Reporting, StageKind::Comitting),
+    (StageKind::Staking, StageKind::Reporting),
+];
+
+impl Feed {
+    /// What a round costs the lease: its reward plus a bounty for each of
+    /// the five stage advances.
+    pub fn round_cost(&self) -> u64 {
+        self.checked_round_cost().unwrap()
+    }
+
+    /// `round_cost`, or None if it overflows, which `config_is_valid`
+    /// rules out.
+    pub fn checked_round_cost(&self) -> Option<u64> {
+        self.crank_bounty
+            .checked_mul(5)?
+            .checked_add(self.reward_per_round)
+    }
+
+    /// The round the feed currently points at for `stage`.
+    pub fn round_for(&self, stage: StageKind) -> Option<Pubkey> {
+        match stage {
+            StageKind::Staking => self.staking_round,
+            StageKind::Reporting => self.reporting_round,
+            StageKind::Comitting => self.committing_round,
+            StageKind::Certifying => self.certifying_round,
+            StageKind::Finalizing => self.finalizing_round,
+            _ => None,
+        }
+    }
+
+    fn set_round_for(&mut self, stage: StageKind, round: Pubkey) {
+        match stage {
+            StageKind::Staking => self.staking_round = Some(round),
+            StageKind::Reporting => self.reporting_round = Some(round),
+            StageKind::Comitting => self.committing_round = Some(round),
+            StageKind::Certifying => self.certifying_round = Some(round),
+            StageKind::Finalizing => self.finalizing_round = Some(round),
+            _ => {}
+        }
+    }
+}
+
+impl Round {
+    /// Slot at which the round last entered `stage`.
+    pub fn start_timestamp(&self, stage: StageKind) -> u64 {
+        match stage {
+            StageKind::Staking => self.staking_start_timestamp,
+            StageKind::Reporting => self.reporting_start_timestamp,
+            StageKind::Comitting => self.committing_start_timestamp,
+            StageKind::Certifying => self.certifying_start_timestamp,
+            StageKind::Finalizing => self.finalizing_start_timestamp,
+            _ => 0,
+        }
+    }
+
+    fn set_start_timestamp(&mut self, stage: StageKind, timestamp: u64) {
+        match stage {
+            StageKind::Staking => self.staking_start_timestamp = timestamp,
+            StageKind::Reporting => self.reporting_start_timestamp = timestamp,
+            StageKind::Comitting => self.committing_start_timestamp = timestamp,
+            StageKind::Certifying => self.certifying_start_timestamp = timestamp,
+            StageKind::Finalizing => self.finalizing_start_timestamp = timestamp,
+            _ => {}
+        }
+    }
+
+    /// Marks the crank bounty for moving into `stage` as paid at the
+    /// current height. Returns false if it already was.
+    fn take_bounty(&mut self, stage: StageKind) -> bool {
+        let bit = 1u8 << (stage as u8);
+        let unpaid = self.bounties_paid & bit == 0;
+        self.bounties_paid |= bit;
+        unpaid
+    }
+
+    /// Whether the round has spent a full update interval in its current
+    /// stage and is due to move on.
+    pub fn stage_elapsed(&self, timestamp: u64) -> bool {
+        self.stage_start_timestamp()
+            .checked_add(self.update_interval.into())
+            .unwrap() < timestamp
+    }
+}
+
+fn in_progress_error(stage: StageKind) -> StarboardError {
+    match stage {
+        StageKind::Staking => StarboardError::StakingInProgress,
+        StageKind::Reporting => StarboardError::ReportingInProgress,
+        StageKind::Comitting => StarboardError::ComittingInProgress,
+        StageKind::Certifying => StarboardError::CertifyingInProgress,
+        _ => StarboardError::FinalizingInProgress,
+    }
+}
+
+/// Whether the feed's `stage` slot can be taken over. `old_round` is the
+/// round the feed points at for that stage, or None when the pointer is
+/// empty or already names the round being advanced.
+pub fn slot_is_free(stage: StageKind, old_round: Option<&Round>, timestamp: u64) -> bool {
+    match old_round {
+        Some(old_round) => old_round
+            .start_timestamp(stage)
+            .checked_add(old_round.update_interval.into())
+            .unwrap() < timestamp,
+        None => true,
+    }
+}
+
+/// Loads the round currently holding `stage` from `remaining_accounts`
+/// when the feed points somewhere other than `round_key`. The account
+/// must be the exact round the feed points at, and must belong to the
+/// feed, so a stale round cannot be used to skip the interval check.
+pub fn previous_round(
+    feed: &Feed,
+    feed_key: Pubkey,
+    stage: StageKind,
+    round_key: Pubkey,
+    remaining_accounts: &[AccountInfo],
+) -> Result<Option<Round>> {
+    let pointer = match feed.round_for(stage) {
+        Some(pointer) if pointer != round_key => pointer,
+        _ => return Ok(None),
+    };
+    let old_round_info = match remaining_accounts.first() {
+        Some(info) => info,
+        None => return err!(StarboardError::MissingPreviousRound),
+    };
+    require_keys_eq!(old_round_info.key(), pointer, StarboardError::PreviousRoundMismatch);
+
+    let old_round = AccountLoader::<Round>::try_from(old_round_info)?;
+    let old_round_data = *old_round.load()?;
+    require_keys_eq!(old_round_data.feed, feed_key, StarboardError::PreviousRoundFeedMismatch);
+    Ok(Some(old_round_data))
+}
+
+/// Moves `round` into `to` and points the feed's `to` slot at it. This is
+/// the body shared by every `start_*` instruction and by `crank`.
+pub fn advance(
+    feed: &mut Feed,
+    round: &mut Round,
+    round_key: Pubkey,
+    to: StageKind,
+    old_round: Option<&Round>,
+    timestamp: u64,
+) -> Result<()> {
+    round.transition(to)?;
+    if !slot_is_free(to, old_round, timestamp) {
+        return err!(in_progress_error(to));
+    }
+
+    feed.set_round_for(to, round_key);
+    round.set_start_timestamp(to, timestamp);
+    if to == StageKind::Staking {
+        feed.height = feed.height.checked_add(1u64).unwrap();
+        round.round_height = feed.height;
+        round.bounties_paid = 0;
+        round.update_interval = feed.update_interval;
+        round.min_stake = feed.min_stake;
+        round.max_stake = feed.max_stake;
+        round.aggregation = feed.aggregation;
+        round.trim_bps = feed.trim_bps;
+        round.min_reporters = feed.min_reporters;
+        round.max_reporters = feed.max_reporters;
+        round.slash_band_bps = feed.slash_band_bps;
+        round.slash_mad_multiple = feed.slash_mad_multiple;
+        msg!("round height: {}", round.round_height);
+    }
+    msg!("{} started at {}", to, timestamp);
+    Ok(())
+}
+
+/// Bounty owed to the keeper that just moved `round` into `stage`. Only
+/// the first advance into each stage of a height is paid, and only out
+/// of `available`, the lease balance left, which is reduced by the
+/// amount returned.
+pub fn crank_bounty(feed: &Feed, round: &mut Round, stage: StageKind, available: &mut u64) -> u64 {
+    let bounty = feed.crank_bounty;
+    if bounty == 0 || *available < bounty || !round.take_bounty(stage) {
+        return 0;
+    }
+    *available -= bounty;
+    bounty
+}
+
+pub fn pay_crank_bounty<'info>(
+    lease: &Account<'info, TokenAccount>,
+    keeper_tokens: &Account<'info, TokenAccount>,
+    program_as_signer: &UncheckedAccount<'info>,
+    token_program: &Program<'info, Token>,
+    bump: u8,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let signer_seeds: &[&[&[u8]]] = &[&[b"program", b"signer", &[bump]]];
+    let transfer_accounts = Transfer {
+        from: lease.to_account_info(),
+        to: keeper_tokens.to_account_info(),
+        authority: program_as_signer.to_account_info(),
+    };
+    let cpi_context = CpiContext::new_with_signer(token_program.to_account_info(), transfer_accounts, signer_seeds);
+    transfer(cpi_context, amount)?;
+    msg!("Paid crank bounty {} to {}", amount, keeper_tokens.key());
+    Ok(())
+}
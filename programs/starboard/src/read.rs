@@ -0,0 +1,120 @@
+use crate::*;
+
+/// What `read_feed` returns to the calling program through return data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeedReading {
+    pub value: i64,
+    pub confidence: u64,
+    // the price is `value * 10^exponent`
+    pub exponent: i32,
+    // slot the value was finalized at
+    pub slot: u64,
+    pub round_height: u64,
+}
+
+impl Feed {
+    /// The feed's latest value, failing unless it was finalized by the
+    /// last round and no more than `max_age_slots` before `slot`.
+    pub fn reading(&self, slot: u64, max_age_slots: u64) -> Result<FeedReading> {
+        require!(self.latest_finalized_round_height > 0, StarboardError::NoFinalizedValue);
+        require!(!self.stale, StarboardError::LastRoundFailed);
+        let age = slot.saturating_sub(self.latest_finalized_timestamp);
+        if age > max_age_slots {
+            return Err(error!(StarboardError::ValueTooOld).with_values((age, max_age_slots)));
+        }
+        Ok(FeedReading {
+            value: self.latest_finalized_value,
+            confidence: self.latest_finalized_confidence,
+            exponent: self.exponent,
+            slot: self.latest_finalized_timestamp,
+            round_height: self.latest_finalized_round_height,
+        })
+    }
+}
+
+/// Reads `feed` from another program by CPI into `read_feed`, so a
+/// consumer can do `let reading = read_feed_cpi(starboard_program, feed, starboard, 150)?;`.
+/// `starboard` is the protocol config PDA, checked for the protocol pause.
+#[cfg(feature = "cpi")]
+pub fn read_feed_cpi<'info>(
+    starboard_program: AccountInfo<'info>,
+    feed: AccountInfo<'info>,
+    starboard: AccountInfo<'info>,
+    max_age_slots: u64,
+) -> Result<FeedReading> {
+    let accounts = crate::cpi::accounts::ReadFeed { feed, starboard };
+    let reading = crate::cpi::read_feed(CpiContext::new(starboard_program, accounts), max_age_slots)?;
+    Ok(reading.get())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+    use anchor_lang::__private::bytemuck;
+
+    // the starboard-consumer crate decodes this fixture by hand
+    const FEED_FIXTURE: &[u8] = include_bytes!("../../../crates/starboard-consumer/fixtures/feed_v4.bin");
+
+    fn fixture_feed() -> Feed {
+        let mut feed: Feed = bytemuck::Zeroable::zeroed();
+        feed.version = FEED_LAYOUT_VERSION;
+        feed.description[..13].copy_from_slice(b"SOL/USD basis");
+        feed.lease = Pubkey::new_from_array([1; 32]);
+        feed.lease_authority = Pubkey::new_from_array([2; 32]);
+        feed.latest_finalized_value = -4_250;
+        feed.latest_finalized_confidence = 75;
+        feed.latest_finalized_timestamp = 1_000;
+        feed.latest_finalized_round_height = 41;
+        feed.update_interval = 30;
+        feed.exponent = -4;
+        feed.staking_round = Some(Pubkey::new_from_array([3; 32]));
+        feed.finalizing_round = Some(Pubkey::new_from_array([4; 32]));
+        feed.bump = 254;
+        feed.started = true;
+        feed.height = 45;
+        feed.min_stake = 5;
+        feed.cert_bond = 5;
+        feed.vault = Pubkey::new_from_array([5; 32]);
+        feed.stake_mint = NATIVE_MINT;
+        feed.slash_band_bps = 500;
+        feed.slash_mad_multiple = 3;
+        feed.min_reporters = 1;
+        feed.authority = Pubkey::new_from_array([6; 32]);
+        feed.aggregation = AggregationKind::TrimmedMean as u8;
+        feed.trim_bps = 1000;
+        feed.max_reporters = 20;
+        feed
+    }
+
+    fn account_bytes(feed: &Feed) -> Vec<u8> {
+        let mut data = Feed::discriminator().to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(feed));
+        data
+    }
+
+    #[test]
+    fn consumer_fixture_matches_feed_layout() {
+        // a failure here means the Feed layout changed: bump
+        // FEED_LAYOUT_VERSION and teach starboard-consumer the new layout
+        assert_eq!(account_bytes(&fixture_feed()), FEED_FIXTURE);
+    }
+
+    #[test]
+    fn reading_checks_age_and_failures() {
+        let mut feed = fixture_feed();
+        let reading = feed.reading(1_100, 100).unwrap();
+        assert_eq!(reading.value, -4_250);
+        assert_eq!(reading.confidence, 75);
+        assert_eq!(reading.exponent, -4);
+        assert_eq!(reading.slot, 1_000);
+        assert_eq!(reading.round_height, 41);
+        assert_eq!(feed.reading(1_101, 100).unwrap_err(), error!(StarboardError::ValueTooOld));
+
+        feed.stale = true;
+        assert_eq!(feed.reading(1_000, 100).unwrap_err(), error!(StarboardError::LastRoundFailed));
+
+        feed.latest_finalized_round_height = 0;
+        assert_eq!(feed.reading(1_000, 100).unwrap_err(), error!(StarboardError::NoFinalizedValue));
+    }
+}
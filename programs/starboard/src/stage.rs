@@ -0,0 +1,198 @@
+use crate::*;
+use std::fmt;
+
+/// Stage of a pipelined Round, stored as `Round.current_stage`.
+///
+/// A round starts out in `Standby`, walks through the five pipeline
+/// stages and rests in `Finalized` or `Failed` until it is picked up
+/// again by `start_staking`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum StageKind {
+    Standby,
+    Staking,
+    Reporting,
+    Comitting,
+    Certifying,
+    Finalizing,
+    Finalized,
+    Failed,
+}
+
+impl StageKind {
+    /// Stages a round may move into `self` from. This is the single
+    /// transition table every instruction goes through.
+    pub fn allowed_from(self) -> &'static [StageKind] {
+        use StageKind::*;
+        match self {
+            Standby => &[],
+            Staking => &[Standby, Finalized, Failed],
+            Reporting => &[Staking],
+            Comitting => &[Reporting],
+            Certifying => &[Comitting],
+            Finalizing => &[Certifying],
+            Finalized => &[Finalizing],
+            Failed => &[Staking, Reporting, Comitting, Certifying, Finalizing],
+        }
+    }
+
+    pub fn can_transition(from: StageKind, to: StageKind) -> bool {
+        to.allowed_from().contains(&from)
+    }
+
+    /// Whether the round is not working on any height.
+    pub fn is_idle(self) -> bool {
+        matches!(self, StageKind::Standby | StageKind::Finalized | StageKind::Failed)
+    }
+}
+
+impl TryFrom<u8> for StageKind {
+    type Error = anchor_lang::error::Error;
+
+    fn try_from(stage: u8) -> Result<Self> {
+        use StageKind::*;
+        Ok(match stage {
+            0 => Standby,
+            1 => Staking,
+            2 => Reporting,
+            3 => Comitting,
+            4 => Certifying,
+            5 => Finalizing,
+            6 => Finalized,
+            7 => Failed,
+            _ => return err!(StarboardError::InvalidStage),
+        })
+    }
+}
+
+impl fmt::Display for StageKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+fn describe(stages: &[StageKind]) -> String {
+    stages.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(" or ")
+}
+
+impl Round {
+    pub fn stage(&self) -> Result<StageKind> {
+        StageKind::try_from(self.current_stage)
+    }
+
+    /// Fails with `WrongStage`, naming both stages, unless the round is
+    /// in `expected`.
+    pub fn require_stage(&self, expected: StageKind) -> Result<()> {
+        let actual = self.stage()?;
+        if actual != expected {
+            return Err(error!(StarboardError::WrongStage).with_values((expected, actual)));
+        }
+        Ok(())
+    }
+
+    /// Moves the round into `to` if the transition table allows it.
+    pub fn transition(&mut self, to: StageKind) -> Result<StageKind> {
+        let from = self.stage()?;
+        if !StageKind::can_transition(from, to) {
+            msg!("Cannot move round from {} to {}", from, to);
+            return Err(error!(StarboardError::InvalidStageTransition)
+                .with_values((describe(to.allowed_from()), from)));
+        }
+        self.current_stage = to as u8;
+        Ok(from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::error::{AnchorError, ComparedValues};
+    use StageKind::*;
+
+    const ALL: [StageKind; 8] = [
+        Standby, Staking, Reporting, Comitting, Certifying, Finalizing, Finalized, Failed,
+    ];
+
+    fn round_in(stage: StageKind) -> Round {
+        let mut round: Round = anchor_lang::__private::bytemuck::Zeroable::zeroed();
+        round.current_stage = stage as u8;
+        round
+    }
+
+    #[test]
+    fn pipeline_walks_forward() {
+        let mut round = round_in(Standby);
+        for stage in [Staking, Reporting, Comitting, Certifying, Finalizing, Finalized, Staking] {
+            round.transition(stage).unwrap();
+            assert_eq!(round.stage().unwrap(), stage);
+        }
+    }
+
+    #[test]
+    fn finalizing_is_not_certifying() {
+        assert!(StageKind::can_transition(Certifying, Finalizing));
+        assert!(!StageKind::can_transition(Finalizing, Finalizing));
+        assert!(!StageKind::can_transition(Certifying, Certifying));
+    }
+
+    #[test]
+    fn stages_cannot_be_skipped() {
+        assert!(!StageKind::can_transition(Standby, Reporting));
+        assert!(!StageKind::can_transition(Staking, Comitting));
+        assert!(!StageKind::can_transition(Certifying, Finalized));
+        assert!(!StageKind::can_transition(Finalized, Reporting));
+    }
+
+    #[test]
+    fn only_active_rounds_can_fail() {
+        for stage in ALL {
+            assert_eq!(StageKind::can_transition(stage, Failed), !stage.is_idle());
+        }
+    }
+
+    #[test]
+    fn idle_rounds_restart_staking() {
+        for stage in ALL {
+            assert_eq!(StageKind::can_transition(stage, Staking), stage.is_idle());
+        }
+    }
+
+    #[test]
+    fn nothing_returns_to_standby() {
+        for stage in ALL {
+            assert!(!StageKind::can_transition(stage, Standby));
+        }
+    }
+
+    #[test]
+    fn illegal_transition_leaves_stage_untouched() {
+        let mut round = round_in(Reporting);
+        let err = round.transition(Finalizing).unwrap_err();
+        assert_eq!(err, error!(StarboardError::InvalidStageTransition));
+        assert_eq!(round.stage().unwrap(), Reporting);
+    }
+
+    #[test]
+    fn require_stage_names_both_stages() {
+        let round = round_in(Staking);
+        assert!(round.require_stage(Staking).is_ok());
+        match round.require_stage(Reporting).unwrap_err() {
+            anchor_lang::error::Error::AnchorError(AnchorError {
+                compared_values: Some(ComparedValues::Values((expected, actual))),
+                ..
+            }) => {
+                assert_eq!(expected, "Reporting");
+                assert_eq!(actual, "Staking");
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn u8_round_trip() {
+        for stage in ALL {
+            assert_eq!(StageKind::try_from(stage as u8).unwrap(), stage);
+        }
+        assert!(StageKind::try_from(8).is_err());
+    }
+}
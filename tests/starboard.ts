@@ -4,25 +4,69 @@ import { Starboard } from "../target/types/starboard";
 import {
   Keypair,
   PublicKey,
-  TransactionInstruction,
-  Transaction,
-  sendAndConfirmTransaction,
-  SystemProgram,
-  SYSVAR_RENT_PUBKEY,
-  LAMPORTS_PER_SOL,
 } from "@solana/web3.js";
 import {
+  NATIVE_MINT,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddress,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import {
+  wrapSol,
+  fundedUser,
+  expectError,
+  initStarboard,
+  updateStarboard,
   createFeed,
   createRound,
   startFeed,
   startStaking,
   stake,
+  topUpStake,
+  startReporting,
+  report,
+  startCommitting,
+  commitmentHash,
+  committ,
+  startCertifying,
+  reveal,
+  certify,
+  startFinalizing,
+  finalize,
+  slashReporter,
+  slashCertifier,
+  claimRewards,
+  claimCertRewards,
+  withdrawStake,
+  failRound,
+  closeReport,
+  crank,
+  fundLease,
+  withdrawLease,
+  setFeedConfig,
+  transferFeedAuthority,
+  setProtocolPaused,
+  setFeedPaused,
+  stakeNative,
+  topUpStakeNative,
+  reportNative,
+  certifyNative,
+  withdrawStakeNative,
+  claimRewardsNative,
+  claimCertRewardsNative,
+  closeReportNative,
+  slashReporterNative,
+  slashCertifierNative,
+  readFeed,
 } from './utils';
-import { 
-  TOKEN_PROGRAM_ID,
-  getOrCreateAssociatedTokenAccount,
-} from '@solana/spl-token';
-import {NATIVE_MINT, TOKEN_PROGRAM_ID, getOrCreateAssociatedTokenAccount, createAssociatedTokenAccountInstruction, getAssociatedTokenAddress, createSyncNativeInstruction, getAccount} from "@solana/spl-token";
+const { randomBytes } = require('crypto');
+
+// StageKind as stored in Round.current_stage
+const STAKING = 1;
+const REPORTING = 2;
+const FINALIZED = 6;
+const FAILED = 7;
 
 describe("starboard", () => {
   // Configure the client to use the local cluster.
@@ -30,35 +74,957 @@ describe("starboard", () => {
 
   const program = anchor.workspace.Starboard as Program<Starboard>;
   const payer = (program.provider as anchor.AnchorProvider).wallet.payer;
+  const connection = program.provider.connection;
 
-  it("Initializes a feed", async () => {
-
-    const voterAta = await getOrCreateAssociatedTokenAccount(program.provider.connection, payer, new PublicKey("So11111111111111111111111111111111111111112"), payer.publicKey);
-    const solTransferTransaction = new Transaction()
-      .add(
-        SystemProgram.transfer({
-          fromPubkey: payer.publicKey,
-          toPubkey: voterAta.address,
-          lamports: LAMPORTS_PER_SOL
-        }),
-        createSyncNativeInstruction(
-          voterAta.address
-        )
-      );
-    await sendAndConfirmTransaction(program.provider.connection, solTransferTransaction, [payer]);
+  async function tokenBalance(address: PublicKey): Promise<number> {
+    return Number((await getAccount(connection, address)).amount);
+  }
+
+  async function wsolBalance(owner: PublicKey): Promise<number> {
+    return tokenBalance(await getAssociatedTokenAddress(NATIVE_MINT, owner));
+  }
+
+  async function vaultBalance(feed: PublicKey): Promise<number> {
+    return tokenBalance((await program.account.feed.fetch(feed)).vault);
+  }
+
+  async function exists(address: PublicKey): Promise<boolean> {
+    return (await connection.getAccountInfo(address)) !== null;
+  }
+
+  async function waitSlots(slots: number) {
+    const target = (await connection.getSlot()) + slots;
+    while ((await connection.getSlot()) <= target) {
+      await new Promise((resolve) => setTimeout(resolve, 200));
+    }
+  }
+
+  async function commitReport(user: Keypair, feed: PublicKey, value: number, confidence: number): Promise<Buffer> {
+    const salt = randomBytes(32);
+    const feedData = await program.account.feed.fetch(feed);
+    const roundData = await program.account.round.fetch(feedData.committingRound);
+    const commitment = commitmentHash(feed, roundData.roundHeight.toNumber(), user.publicKey, value, confidence, salt);
+    await committ(program, user, feed, commitment);
+    return salt;
+  }
+
+  type Reporter = {
+    user: Keypair,
+    value: number,
+    confidence?: number,
+    stake?: number,
+    reveal?: boolean,
+    certify?: boolean,
+  };
 
-    const feed = await createFeed(program, payer, "SOL/USD", 30);
-    console.log(`Feed: ${feed}`);
+  // Walks `round` from staking into finalizing: every reporter stakes,
+  // reports and commits, the ones not marked otherwise reveal, and
+  // `certifier` certifies every revealed report not marked otherwise.
+  async function playRound(feed: PublicKey, round: PublicKey, reporters: Reporter[], certifier: Keypair, native: boolean = false) {
+    await startStaking(program, feed, round);
+    for (const r of reporters) {
+      if (native) {
+        await stakeNative(program, r.user, feed, r.stake ?? null);
+      } else {
+        await stake(program, r.user, feed, r.stake ?? null);
+      }
+    }
+    await startReporting(program, feed, round);
+    const records = [];
+    for (const r of reporters) {
+      records.push(native ? await reportNative(program, r.user, feed) : await report(program, r.user, feed));
+    }
+    await startCommitting(program, feed, round);
+    const salts = [];
+    for (const r of reporters) {
+      salts.push(await commitReport(r.user, feed, r.value, r.confidence ?? 0));
+    }
+    await startCertifying(program, feed, round);
+    const certRecords = [];
+    for (const [i, r] of reporters.entries()) {
+      if (r.reveal !== false) {
+        await reveal(program, r.user, feed, r.value, r.confidence ?? 0, salts[i]);
+      }
+      if (r.reveal === false || r.certify === false) {
+        certRecords.push(null);
+        continue;
+      }
+      certRecords.push(native
+        ? await certifyNative(program, certifier, feed, records[i])
+        : await certify(program, certifier, feed, records[i]));
+    }
+    await startFinalizing(program, feed, round);
+    return { records, certRecords };
+  }
 
-    let rounds = [];
-    for (var i = 0; i<5; i++) {
-      let key = await createRound(program, payer, feed, i);
-      rounds[i] = key;
-      console.log(`round ${i}: ${key}`);
+  // Finalizes one round of `reports` on a fresh feed and returns the feed.
+  async function aggregateWith(aggregation: number, trimBps: number, reports: { value: number, confidence: number }[]) {
+    const feed = await createFeed(program, payer, "AGG/USD", 1000, {aggregation, trimBps});
+    const round = await createRound(program, payer, feed, 0);
+    const reporters = [];
+    for (const r of reports) {
+      reporters.push({ user: await fundedUser(program, 1000), value: r.value, confidence: r.confidence });
     }
+    const { records } = await playRound(feed, round, reporters, await fundedUser(program, 1000));
+    await finalize(program, feed, records);
+    return await program.account.feed.fetch(feed);
+  }
+
+  before(async () => {
+    // the provider wallet funds leases and collects crank bounties
+    await wrapSol(program, payer, anchor.web3.LAMPORTS_PER_SOL);
+  });
+
+  describe("protocol config", () => {
+    it("rejects initialization by anyone but the upgrade authority", async () => {
+      const stranger = await fundedUser(program);
+      await expectError(program, initStarboard(program, stranger, stranger.publicKey), "NotUpgradeAuthority");
+    });
+
+    it("initializes the protocol config", async () => {
+      await initStarboard(program, payer, payer.publicKey);
+      const starboard = await program.account.starboard.all();
+      assert.equal(starboard.length, 1);
+      assert.ok(starboard[0].account.admin.equals(payer.publicKey));
+      assert.ok(starboard[0].account.defaultStakeMint.equals(NATIVE_MINT));
+    });
+
+    it("lets only the admin update the protocol config", async () => {
+      const stranger = await fundedUser(program);
+      await expectError(program, updateStarboard(program, stranger, {protocolFeeBps: 100}), "ConstraintHasOne");
+      await updateStarboard(program, payer, {defaultSlashBandBps: 400});
+      const starboard = (await program.account.starboard.all())[0].account;
+      assert.equal(starboard.defaultSlashBandBps, 400);
+      await updateStarboard(program, payer, {defaultSlashBandBps: 500});
+    });
+  });
+
+  describe("report", () => {
+    let feed: PublicKey;
+    let reporter: Keypair;
+
+    before(async () => {
+      feed = await createFeed(program, payer, "REPORT/USD", 1000);
+      const round = await createRound(program, payer, feed, 0);
+      reporter = await fundedUser(program, 1000);
+      await startStaking(program, feed, round);
+      await stake(program, reporter, feed);
+      await startReporting(program, feed, round);
+    });
+
+    it("registers a ReportRecord for a staked reporter", async () => {
+      const record = await report(program, reporter, feed);
+      const recordData = await program.account.reportRecord.fetch(record);
+      assert.ok(recordData.reporter.equals(reporter.publicKey));
+      assert.equal(recordData.stake.toNumber(), 5);
+      assert.isFalse(recordData.revealed);
+      const roundData = await program.account.round.fetch(recordData.round);
+      assert.equal(roundData.numStakers, 1);
+      assert.equal(roundData.numReports, 1);
+    });
+
+    it("rejects reports from someone who did not stake", async () => {
+      const stranger = await fundedUser(program, 1000);
+      await expectError(program, report(program, stranger, feed), "AccountNotInitialized");
+    });
+  });
+
+  describe("commit and reveal", () => {
+    const value = 4200;
+    const confidence = 7;
+    let feed: PublicKey;
+    let round: PublicKey;
+    let reporter: Keypair;
+    let record: PublicKey;
+    let salt: Buffer;
+
+    before(async () => {
+      feed = await createFeed(program, payer, "COMMIT/USD", 1000);
+      round = await createRound(program, payer, feed, 0);
+      reporter = await fundedUser(program, 1000);
+      await startStaking(program, feed, round);
+      await stake(program, reporter, feed);
+      await startReporting(program, feed, round);
+      record = await report(program, reporter, feed);
+      await startCommitting(program, feed, round);
+    });
+
+    it("stores a sealed commitment", async () => {
+      salt = await commitReport(reporter, feed, value, confidence);
+      const escrow = (await program.account.reportRecord.fetch(record)).escrow;
+      assert.isTrue((await program.account.escrow.fetch(escrow)).committed);
+    });
+
+    it("rejects a reveal that does not match the commitment", async () => {
+      await startCertifying(program, feed, round);
+      await expectError(program, reveal(program, reporter, feed, value + 1, confidence, salt), "CommitmentMismatch");
+      await expectError(program, reveal(program, reporter, feed, value, confidence, randomBytes(32)), "CommitmentMismatch");
+    });
+
+    it("reveals the committed value and confidence", async () => {
+      await reveal(program, reporter, feed, value, confidence, salt);
+      const recordData = await program.account.reportRecord.fetch(record);
+      assert.isTrue(recordData.revealed);
+      assert.equal(recordData.value.toNumber(), value);
+      assert.equal(recordData.confidence.toNumber(), confidence);
+    });
+  });
+
+  describe("certify", () => {
+    let feed: PublicKey;
+    let round: PublicKey;
+    let reporter: Keypair;
+    let certifier: Keypair;
+    let record: PublicKey;
+    let salt: Buffer;
+
+    before(async () => {
+      feed = await createFeed(program, payer, "CERTIFY/USD", 1000);
+      round = await createRound(program, payer, feed, 0);
+      reporter = await fundedUser(program, 1000);
+      certifier = await fundedUser(program, 1000);
+      await startStaking(program, feed, round);
+      await stake(program, reporter, feed);
+      await startReporting(program, feed, round);
+      record = await report(program, reporter, feed);
+      await startCommitting(program, feed, round);
+      salt = await commitReport(reporter, feed, 100, 0);
+      await startCertifying(program, feed, round);
+    });
+
+    it("rejects certifying a report before it is revealed", async () => {
+      await expectError(program, certify(program, certifier, feed, record), "ReportNotRevealed");
+    });
+
+    it("rejects certifying your own report", async () => {
+      await reveal(program, reporter, feed, 100, 0, salt);
+      await expectError(program, certify(program, reporter, feed, record), "SelfCertification");
+    });
+
+    it("bonds a certification behind the report", async () => {
+      const certRecord = await certify(program, certifier, feed, record);
+      assert.equal((await program.account.certRecord.fetch(certRecord)).bond.toNumber(), 5);
+      assert.equal((await program.account.reportRecord.fetch(record)).numCertifications, 1);
+      assert.equal((await program.account.round.fetch(round)).numCertified, 1);
+    });
+  });
+
+  describe("finalize", () => {
+    let feed: PublicKey;
+    let round: PublicKey;
+    let records: PublicKey[];
+
+    before(async () => {
+      feed = await createFeed(program, payer, "FINALIZE/USD", 1000);
+      round = await createRound(program, payer, feed, 0);
+      const reporters = [];
+      for (const value of [100, 101, 102]) {
+        reporters.push({ user: await fundedUser(program, 1000), value });
+      }
+      ({ records } = await playRound(feed, round, reporters, await fundedUser(program, 1000)));
+    });
+
+    it("rejects a finalize that leaves out reports", async () => {
+      await expectError(program, finalize(program, feed, records.slice(0, 2)), "MissingReports");
+    });
+
+    it("rejects a report passed twice", async () => {
+      await expectError(program, finalize(program, feed, [records[0], records[0], records[1]]), "DuplicateReport");
+    });
+
+    it("finalizes the certified value into the feed", async () => {
+      await finalize(program, feed, records);
+      const feedData = await program.account.feed.fetch(feed);
+      assert.equal(feedData.latestFinalizedValue.toNumber(), 101);
+      assert.equal(feedData.latestFinalizedRoundHeight.toNumber(), 1);
+      assert.equal((await program.account.round.fetch(round)).currentStage, FINALIZED);
+    });
+  });
+
+  describe("slashing", () => {
+    let feed: PublicKey;
+    let round: PublicKey;
+    let honest: Keypair[];
+    let outlier: Keypair;
+    let silent: Keypair;
+    let lonely: Keypair;
+    let records: PublicKey[];
+    let certRecords: PublicKey[];
+
+    before(async () => {
+      feed = await createFeed(program, payer, "SLASH/USD", 1000);
+      round = await createRound(program, payer, feed, 0);
+      honest = [await fundedUser(program, 1000), await fundedUser(program, 1000), await fundedUser(program, 1000)];
+      outlier = await fundedUser(program, 1000);
+      silent = await fundedUser(program, 1000);
+      lonely = await fundedUser(program, 1000);
+    });
+
+    it("rejects a finalize that leaves out an unrevealed report", async () => {
+      ({ records, certRecords } = await playRound(feed, round, [
+        { user: honest[0], value: 100 },
+        { user: honest[1], value: 101 },
+        { user: honest[2], value: 100 },
+        { user: outlier, value: 1000 },
+        { user: silent, value: 100, reveal: false },
+        { user: lonely, value: 100, certify: false },
+      ], await fundedUser(program, 1000)));
+      await expectError(program, finalize(program, feed, records.filter((_, i) => i != 4)), "MissingReports");
+    });
+
+    it("flags outliers and reporters who never revealed", async () => {
+      await finalize(program, feed, records);
+      assert.equal((await program.account.feed.fetch(feed)).latestFinalizedValue.toNumber(), 100);
+      for (const record of records.slice(0, 3)) {
+        assert.isFalse((await program.account.reportRecord.fetch(record)).slashed);
+      }
+      assert.isTrue((await program.account.reportRecord.fetch(records[3])).slashed);
+      assert.isTrue((await program.account.reportRecord.fetch(records[4])).slashed);
+      assert.isFalse((await program.account.reportRecord.fetch(records[5])).slashed);
+    });
+
+    it("refunds reports nobody certified without a reward", async () => {
+      await expectError(program, claimRewards(program, lonely, feed, records[5]), "ReportNotCertified");
+      const before = await wsolBalance(lonely.publicKey);
+      await withdrawStake(program, lonely, feed, round);
+      assert.equal(await wsolBalance(lonely.publicKey), before + 5);
+    });
+
+    it("rejects slashing reports that were not flagged", async () => {
+      await expectError(program, slashReporter(program, feed, records[0]), "NotSlashed");
+    });
+
+    it("moves slashed stake and bonds into the vault", async () => {
+      const before = await vaultBalance(feed);
+      await slashReporter(program, feed, records[3]);
+      await slashCertifier(program, feed, certRecords[3]);
+      await slashReporter(program, feed, records[4]);
+      assert.equal(await vaultBalance(feed), before + 5 + 5 + 5);
+      await closeReport(program, outlier, feed, records[3]);
+      assert.isFalse(await exists(records[3]));
+    });
+
+    it("rejects slashing a later stake through an old report", async () => {
+      await startStaking(program, feed, round);
+      await stake(program, silent, feed);
+      await expectError(program, slashReporter(program, feed, records[4]), "EscrowRoundMismatch");
+    });
+
+    it("caps the reports a round takes", async () => {
+      await expectError(program, createFeed(program, payer, "CAP/USD", 1000, {maxReporters: 21}), "InvalidFeedConfig");
+      const capped = await createFeed(program, payer, "CAP/USD", 1000, {maxReporters: 1});
+      await expectError(program, setFeedConfig(program, payer, capped, {maxReporters: 0}), "InvalidFeedConfig");
+      const cappedRound = await createRound(program, payer, capped, 0);
+      const first = await fundedUser(program, 1000);
+      const second = await fundedUser(program, 1000);
+      await startStaking(program, capped, cappedRound);
+      await stake(program, first, capped);
+      await stake(program, second, capped);
+      await startReporting(program, capped, cappedRound);
+      await report(program, first, capped);
+      await expectError(program, report(program, second, capped), "RoundFull");
+    });
+  });
+
+  describe("rewards", () => {
+    const rewardPerRound = 1000;
+    let feed: PublicKey;
+    let round: PublicKey;
+    let honest: Keypair[];
+    let outlier: Keypair;
+    let certifier: Keypair;
+    let records: PublicKey[];
+    let certRecords: PublicKey[];
+
+    before(async () => {
+      feed = await createFeed(program, payer, "REWARD/USD", 1000, {rewardPerRound});
+      round = await createRound(program, payer, feed, 0);
+      await fundLease(program, payer, feed, 2 * rewardPerRound);
+      honest = [await fundedUser(program, 1000), await fundedUser(program, 1000), await fundedUser(program, 1000)];
+      outlier = await fundedUser(program, 1000);
+      certifier = await fundedUser(program, 1000);
+      ({ records, certRecords } = await playRound(feed, round, [
+        { user: honest[0], value: 100 },
+        { user: honest[1], value: 101 },
+        { user: honest[2], value: 100 },
+        { user: outlier, value: 1000 },
+      ], certifier));
+      await finalize(program, feed, records);
+    });
+
+    it("pays honest reporters and their certifiers", async () => {
+      const recordData = await program.account.reportRecord.fetch(records[0]);
+      // three honest reports of stake 5 and bond 5 split the lease's reward
+      assert.equal(recordData.reporterReward.toNumber(), Math.floor(rewardPerRound * 5 / 30));
+      const reporterBefore = await wsolBalance(honest[0].publicKey);
+      await claimRewards(program, honest[0], feed, records[0]);
+      assert.equal(await wsolBalance(honest[0].publicKey), reporterBefore + recordData.reporterReward.toNumber());
+
+      const certifierBefore = await wsolBalance(certifier.publicKey);
+      await claimCertRewards(program, certifier, feed, certRecords[0]);
+      assert.equal(await wsolBalance(certifier.publicKey), certifierBefore + 5 + recordData.certReward.toNumber());
+    });
+
+    it("rejects double claims and claims on slashed reports", async () => {
+      await expectError(program, claimRewards(program, honest[0], feed, records[0]), "RewardsAlreadyClaimed");
+      await expectError(program, claimRewards(program, outlier, feed, records[3]), "NoRewards");
+      await expectError(program, claimCertRewards(program, certifier, feed, certRecords[3]), "NoRewards");
+    });
+
+    it("pays collected slashed stake out with the next round", async () => {
+      await slashReporter(program, feed, records[3]);
+      await slashCertifier(program, feed, certRecords[3]);
+      const { records: next } = await playRound(feed, round, [{ user: await fundedUser(program, 1000), value: 100 }], certifier);
+
+      const feedData = await program.account.feed.fetch(feed);
+      const collected = (await vaultBalance(feed)) - feedData.rewardsOwed.toNumber();
+      assert.isAbove(collected, 0);
+      await finalize(program, feed, next);
+      const recordData = await program.account.reportRecord.fetch(next[0]);
+      assert.equal(recordData.reporterReward.toNumber(), Math.floor((rewardPerRound + collected) * 5 / 10));
+    });
+  });
+
+  describe("withdraw stake", () => {
+    let feed: PublicKey;
+    let round: PublicKey;
+    let honest: Keypair;
+    let outlier: Keypair;
+    let records: PublicKey[];
+
+    before(async () => {
+      feed = await createFeed(program, payer, "WITHDRAW/USD", 1000);
+      round = await createRound(program, payer, feed, 0);
+      honest = await fundedUser(program, 1000);
+      outlier = await fundedUser(program, 1000);
+      ({ records } = await playRound(feed, round, [
+        { user: honest, value: 100 },
+        { user: await fundedUser(program, 1000), value: 100 },
+        { user: await fundedUser(program, 1000), value: 100 },
+        { user: outlier, value: 1000 },
+      ], await fundedUser(program, 1000)));
+    });
+
+    it("rejects withdrawing stake before the round settles", async () => {
+      await expectError(program, withdrawStake(program, honest, feed, round), "RoundNotSettled");
+    });
+
+    it("returns the stake once the round has finalized", async () => {
+      await finalize(program, feed, records);
+      const escrow = (await program.account.reportRecord.fetch(records[0])).escrow;
+      const before = await wsolBalance(honest.publicKey);
+      await withdrawStake(program, honest, feed, round);
+      assert.equal(await wsolBalance(honest.publicKey), before + 5);
+      assert.isFalse(await exists(escrow));
+    });
+
+    it("rejects refunding slashed stake", async () => {
+      await expectError(program, withdrawStake(program, outlier, feed, round), "StakeSlashed");
+    });
+  });
+
+  describe("round failure", () => {
+    let feed: PublicKey;
+    let round: PublicKey;
+    let reporter: Keypair;
+
+    before(async () => {
+      feed = await createFeed(program, payer, "FAIL/USD", 1000, {minReporters: 2});
+      round = await createRound(program, payer, feed, 0);
+      reporter = await fundedUser(program, 1000);
+      await startStaking(program, feed, round);
+      await stake(program, reporter, feed);
+    });
+
+    it("rejects failing a round that is on schedule", async () => {
+      await expectError(program, failRound(program, feed, round), "RoundNotFailable");
+    });
+
+    it("fails a round that misses its quorum and refunds its stakes", async () => {
+      await startReporting(program, feed, round);
+      await failRound(program, feed, round);
+      assert.equal((await program.account.round.fetch(round)).currentStage, FAILED);
+      assert.isTrue((await program.account.feed.fetch(feed)).stale);
+
+      const before = await wsolBalance(reporter.publicKey);
+      await withdrawStake(program, reporter, feed, round);
+      assert.equal(await wsolBalance(reporter.publicKey), before + 5);
+    });
+
+    it("keeps a newer value fresh when an older height fails", async () => {
+      const interval = 10;
+      const fast = await createFeed(program, payer, "FAIL/USD fast", interval);
+      const older = await createRound(program, payer, fast, 0);
+      const newer = await createRound(program, payer, fast, 1);
+      // the older height stalls in finalizing without a single staker
+      await startStaking(program, fast, older);
+      await startReporting(program, fast, older);
+      await startCommitting(program, fast, older);
+      await startCertifying(program, fast, older);
+      await startFinalizing(program, fast, older);
+      await waitSlots(interval + 1);
+
+      const { records } = await playRound(fast, newer, [{ user: reporter, value: 100 }], await fundedUser(program, 1000));
+      await finalize(program, fast, records);
+      await failRound(program, fast, older);
+      assert.equal((await program.account.round.fetch(older)).currentStage, FAILED);
+      assert.isFalse((await program.account.feed.fetch(fast)).stale);
+    });
+  });
+
+  describe("recycling", () => {
+    let feed: PublicKey;
+    let round: PublicKey;
+    let reporter: Keypair;
+    let certifier: Keypair;
+    let record: PublicKey;
+    let certRecord: PublicKey;
+
+    before(async () => {
+      feed = await createFeed(program, payer, "RECYCLE/USD", 1000);
+      round = await createRound(program, payer, feed, 0);
+      reporter = await fundedUser(program, 1000);
+      certifier = await fundedUser(program, 1000);
+      const played = await playRound(feed, round, [{ user: reporter, value: 100 }], certifier);
+      [record] = played.records;
+      [certRecord] = played.certRecords;
+    });
+
+    it("rejects closing a report before its round settles", async () => {
+      await expectError(program, closeReport(program, reporter, feed, record), "RoundNotSettled");
+    });
+
+    it("rejects closing a report before its reward is claimed", async () => {
+      await finalize(program, feed, [record]);
+      await claimCertRewards(program, certifier, feed, certRecord);
+      await expectError(program, closeReport(program, reporter, feed, record), "RewardsNotClaimed");
+    });
+
+    it("closes settled reports and restarts the round", async () => {
+      await claimRewards(program, reporter, feed, record);
+      await closeReport(program, reporter, feed, record);
+      assert.isFalse(await exists(record));
+      let roundData = await program.account.round.fetch(round);
+      assert.equal(roundData.currentStage, FINALIZED);
+      assert.equal(roundData.numReports, 0);
+
+      await startStaking(program, feed, round);
+      roundData = await program.account.round.fetch(round);
+      assert.equal(roundData.currentStage, STAKING);
+      assert.equal(roundData.roundHeight.toNumber(), 2);
+    });
+  });
+
+  describe("stage order", () => {
+    let feed: PublicKey;
+    let round: PublicKey;
+
+    before(async () => {
+      feed = await createFeed(program, payer, "ORDER/USD", 1000);
+      round = await createRound(program, payer, feed, 0);
+    });
+
+    it("rejects moving a round out of stage order", async () => {
+      await expectError(program, startCommitting(program, feed, round), "InvalidStageTransition");
+    });
+
+    it("moves a round through its stages in order", async () => {
+      await startStaking(program, feed, round);
+      assert.equal((await program.account.round.fetch(round)).currentStage, STAKING);
+      await startReporting(program, feed, round);
+      assert.equal((await program.account.round.fetch(round)).currentStage, REPORTING);
+    });
+  });
+
+  describe("crank", () => {
+    let feed: PublicKey;
+    let rounds: PublicKey[];
+
+    before(async () => {
+      feed = await createFeed(program, payer, "CRANK/USD", 1000);
+      rounds = [];
+      for (let i = 0; i < 5; i++) {
+        rounds.push(await createRound(program, payer, feed, i));
+      }
+      await startFeed(program, feed);
+    });
+
+    it("cranks an idle round into staking", async () => {
+      await crank(program, feed);
+      assert.ok((await program.account.feed.fetch(feed)).stakingRound.equals(rounds[0]));
+      assert.equal((await program.account.round.fetch(rounds[0])).currentStage, STAKING);
+    });
+
+    it("rejects a crank with nothing due", async () => {
+      await expectError(program, crank(program, feed), "NothingToCrank");
+    });
+  });
+
+  describe("previous round checks", () => {
+    let feed: PublicKey;
+    let second: PublicKey;
+
+    before(async () => {
+      feed = await createFeed(program, payer, "PREVIOUS/USD", 1);
+      const first = await createRound(program, payer, feed, 0);
+      second = await createRound(program, payer, feed, 1);
+      await startStaking(program, feed, first);
+      await waitSlots(2);
+    });
+
+    it("rejects a previous round other than the feed's pointer", async () => {
+      await expectError(program, startStaking(program, feed, second, second), "PreviousRoundMismatch");
+    });
+
+    it("takes the slot over from the round the feed points at", async () => {
+      await startStaking(program, feed, second);
+      assert.ok((await program.account.feed.fetch(feed)).stakingRound.equals(second));
+    });
+  });
+
+  describe("crank bounties", () => {
+    const bounty = 10;
+    let feed: PublicKey;
+    let round: PublicKey;
+
+    before(async () => {
+      feed = await createFeed(program, payer, "BOUNTY/USD", 1000, {crankBounty: bounty});
+      round = await createRound(program, payer, feed, 0);
+    });
+
+    it("rejects starting a round the lease cannot pay for", async () => {
+      await expectError(program, startStaking(program, feed, round), "LeaseUnderfunded");
+    });
+
+    it("pays the keeper that advances a stage", async () => {
+      await fundLease(program, payer, feed, 5 * bounty);
+      const before = await wsolBalance(payer.publicKey);
+      await startStaking(program, feed, round);
+      assert.equal(await wsolBalance(payer.publicKey), before + bounty);
+    });
+  });
+
+  describe("lease", () => {
+    let feed: PublicKey;
+
+    before(async () => {
+      feed = await createFeed(program, payer, "LEASE/USD", 1000);
+    });
+
+    it("funds the lease and lets its authority withdraw", async () => {
+      const feedData = await program.account.feed.fetch(feed);
+      await fundLease(program, payer, feed, 1000);
+      assert.equal(await tokenBalance(feedData.lease), 1000);
+      await withdrawLease(program, payer, feed, 400);
+      assert.equal(await tokenBalance(feedData.lease), 600);
+    });
+
+    it("rejects lease withdrawals by others or beyond the balance", async () => {
+      const stranger = await fundedUser(program, 1000);
+      await expectError(program, withdrawLease(program, stranger, feed, 1), "ConstraintHasOne");
+      await expectError(program, withdrawLease(program, payer, feed, 601), "InsufficientLease");
+    });
+
+    it("rejects feeds whose lease cost per round overflows", async () => {
+      const crankBounty = new anchor.BN("4000000000000000000");
+      await expectError(program, createFeed(program, payer, "LEASE/USD", 1000, {crankBounty}), "InvalidFeedConfig");
+    });
+  });
+
+  describe("feed config", () => {
+    let feed: PublicKey;
+
+    before(async () => {
+      feed = await createFeed(program, payer, "CONFIG/USD", 1000);
+    });
+
+    it("lets the feed authority change the feed config", async () => {
+      await setFeedConfig(program, payer, feed, {minStake: 10, description: "CONFIG/USD v2"});
+      const feedData = await program.account.feed.fetch(feed);
+      assert.equal(feedData.minStake.toNumber(), 10);
+    });
+
+    it("rejects config changes from others and invalid configs", async () => {
+      const stranger = await fundedUser(program);
+      await expectError(program, setFeedConfig(program, stranger, feed, {minStake: 1}), "ConstraintHasOne");
+      await expectError(program, setFeedConfig(program, payer, feed, {updateInterval: 0}), "InvalidFeedConfig");
+      await expectError(program, setFeedConfig(program, payer, feed, {minStake: 20, maxStake: 10}), "InvalidFeedConfig");
+    });
+
+    it("hands the feed to a new authority", async () => {
+      const authority = await fundedUser(program);
+      await transferFeedAuthority(program, payer, feed, authority.publicKey);
+      await setFeedConfig(program, authority, feed, {minStake: 5});
+      await expectError(program, setFeedConfig(program, payer, feed, {minStake: 6}), "ConstraintHasOne");
+    });
+  });
+
+  describe("pauses", () => {
+    let feed: PublicKey;
+    let round: PublicKey;
+
+    before(async () => {
+      feed = await createFeed(program, payer, "PAUSE/USD", 1000);
+      round = await createRound(program, payer, feed, 0);
+    });
+
+    it("lets the feed authority and admin pause and unpause", async () => {
+      await setFeedPaused(program, payer, feed, true);
+      assert.isTrue((await program.account.feed.fetch(feed)).paused);
+      await setFeedPaused(program, payer, feed, false);
+      await setProtocolPaused(program, payer, true);
+      assert.isTrue((await program.account.starboard.all())[0].account.paused);
+      await setProtocolPaused(program, payer, false);
+      await startStaking(program, feed, round);
+    });
+
+    it("rejects pauses by others and writes while paused", async () => {
+      const stranger = await fundedUser(program, 1000);
+      await expectError(program, setFeedPaused(program, stranger, feed, true), "NotFeedAuthorityOrAdmin");
+
+      await setFeedPaused(program, payer, feed, true);
+      await expectError(program, stake(program, stranger, feed), "FeedPaused");
+      await setFeedPaused(program, payer, feed, false);
+
+      await setProtocolPaused(program, payer, true);
+      await expectError(program, stake(program, stranger, feed), "ProtocolPaused");
+      await setProtocolPaused(program, payer, false);
+    });
+  });
+
+  describe("stake mint", () => {
+    let mint: PublicKey;
+
+    before(async () => {
+      mint = await createMint(connection, payer, payer.publicKey, null, 0);
+    });
+
+    it("creates feeds on the protocol's default stake mint", async () => {
+      const feed = await createFeed(program, payer, "MINT/USD", 1000);
+      assert.ok((await program.account.feed.fetch(feed)).stakeMint.equals(NATIVE_MINT));
+    });
+
+    it("creates a feed on a custom mint only when it opts in", async () => {
+      await expectError(
+        program,
+        createFeed(program, payer, "USDC/USD", 1000, {stakeMint: mint, customStakeMint: false}),
+        "StakeMintNotDefault"
+      );
+      const custom = await createFeed(program, payer, "USDC/USD", 1000, {stakeMint: mint});
+      assert.ok((await program.account.feed.fetch(custom)).stakeMint.equals(mint));
+    });
+
+    it("rejects native staking on a mint other than wrapped SOL", async () => {
+      await expectError(
+        program,
+        createFeed(program, payer, "USDC/USD", 1000, {stakeMint: mint, nativeStake: true}),
+        "NativeStakeNeedsNativeMint"
+      );
+    });
+  });
+
+  describe("native staking", () => {
+    let feed: PublicKey;
+    let round: PublicKey;
+    let reporters: Keypair[];
+    let outlier: Keypair;
+    let certifier: Keypair;
+    let records: PublicKey[];
+    let certRecords: PublicKey[];
+
+    before(async () => {
+      feed = await createFeed(program, payer, "NATIVE/USD", 1000, {rewardPerRound: 100, nativeStake: true});
+      round = await createRound(program, payer, feed, 0);
+      await fundLease(program, payer, feed, 100);
+      // no wrapped SOL for any of them
+      reporters = [await fundedUser(program), await fundedUser(program)];
+      outlier = await fundedUser(program);
+      certifier = await fundedUser(program);
+    });
+
+    it("runs a round on lamports alone", async () => {
+      ({ records, certRecords } = await playRound(feed, round, [
+        { user: reporters[0], value: 100 },
+        { user: reporters[1], value: 100 },
+        { user: outlier, value: 500 },
+      ], certifier, true));
+      await finalize(program, feed, records);
+      assert.isTrue((await program.account.reportRecord.fetch(records[2])).slashed);
+
+      const recordData = await program.account.reportRecord.fetch(records[0]);
+      let before = await vaultBalance(feed);
+      await claimRewardsNative(program, reporters[0], feed, records[0]);
+      assert.equal(await vaultBalance(feed), before - recordData.reporterReward.toNumber());
+
+      before = await vaultBalance(feed);
+      await claimCertRewardsNative(program, certifier, feed, certRecords[0]);
+      assert.equal(await vaultBalance(feed), before - recordData.certReward.toNumber());
+      assert.isFalse(await exists(certRecords[0]));
+
+      before = await vaultBalance(feed);
+      await slashReporterNative(program, feed, records[2]);
+      await slashCertifierNative(program, feed, certRecords[2]);
+      assert.equal(await vaultBalance(feed), before + 5 + 5);
+
+      await withdrawStakeNative(program, reporters[0], feed, round);
+      await closeReportNative(program, reporters[0], feed, records[0]);
+      assert.isFalse(await exists(records[0]));
+
+      for (const user of [reporters[0], outlier, certifier]) {
+        assert.isFalse(await exists(await getAssociatedTokenAddress(NATIVE_MINT, user.publicKey)));
+      }
+    });
+
+    it("rejects SPL instructions on native feeds and closing unsettled reports", async () => {
+      await expectError(program, closeReportNative(program, reporters[1], feed, records[1]), "CertificationsNotSettled");
+      const stranger = await fundedUser(program, 1000);
+      await expectError(program, stake(program, stranger, feed), "WrongStakeMode");
+    });
+  });
+
+  describe("variable stakes", () => {
+    let feed: PublicKey;
+    let nativeFeed: PublicKey;
+    let staker: Keypair;
+
+    before(async () => {
+      feed = await createFeed(program, payer, "STAKES/USD", 1000, {minStake: 10, maxStake: 30});
+      nativeFeed = await createFeed(program, payer, "STAKES/USD", 1000, {minStake: 10, maxStake: 30, nativeStake: true});
+      await startStaking(program, feed, await createRound(program, payer, feed, 0));
+      await startStaking(program, nativeFeed, await createRound(program, payer, nativeFeed, 0));
+      staker = await fundedUser(program, 1000);
+    });
+
+    it("stakes a chosen amount and tops it up", async () => {
+      await stake(program, staker, feed, 20);
+      await topUpStake(program, staker, feed, 10);
+      const escrows = await program.account.escrow.all();
+      const escrow = escrows.find((e) => e.account.feed.equals(feed) && e.account.staker.equals(staker.publicKey));
+      assert.equal(escrow.account.amount.toNumber(), 30);
+    });
+
+    it("rejects stakes outside the feed's range", async () => {
+      const other = await fundedUser(program, 1000);
+      await expectError(program, stake(program, other, feed, 9), "StakeBelowMinimum");
+      await expectError(program, topUpStake(program, staker, feed, 1), "StakeAboveCap");
+    });
+
+    it("tops up native stakes in lamports", async () => {
+      await stakeNative(program, staker, nativeFeed, 10);
+      await topUpStakeNative(program, staker, nativeFeed, 5);
+      const escrows = await program.account.escrow.all();
+      const escrow = escrows.find((e) => e.account.feed.equals(nativeFeed) && e.account.staker.equals(staker.publicKey));
+      assert.equal(escrow.account.amount.toNumber(), 15);
+      await expectError(program, topUpStakeNative(program, staker, nativeFeed, 16), "StakeAboveCap");
+    });
+  });
+
+  describe("read_feed", () => {
+    let feed: PublicKey;
+
+    before(async () => {
+      feed = await createFeed(program, payer, "READ/USD", 1000);
+      const round = await createRound(program, payer, feed, 0);
+      const { records } = await playRound(feed, round, [{ user: await fundedUser(program, 1000), value: 100 }], await fundedUser(program, 1000));
+      await finalize(program, feed, records);
+    });
+
+    it("returns the finalized value through read_feed", async () => {
+      const reading = await readFeed(program, feed, 1_000_000);
+      assert.equal(reading.value.toNumber(), 100);
+      assert.equal(reading.roundHeight.toNumber(), 1);
+    });
+
+    it("rejects read_feed without a fresh finalized value", async () => {
+      const fresh = await createFeed(program, payer, "READ/USD", 1000);
+      await expectError(program, readFeed(program, fresh, 1_000_000), "NoFinalizedValue");
+      await expectError(program, readFeed(program, feed, 0), "ValueTooOld");
+    });
+  });
+
+  describe("price representation", () => {
+    // -1.23456789 with a confidence of 0.00000042
+    const value = -123456789;
+    const confidence = 42;
+    let feed: PublicKey;
+    let round: PublicKey;
+    let reporter: Keypair;
+    let record: PublicKey;
+    let salt: Buffer;
+
+    before(async () => {
+      feed = await createFeed(program, payer, "BTC/USD", 1000, {exponent: -8});
+      round = await createRound(program, payer, feed, 0);
+      reporter = await fundedUser(program, 1000);
+      await startStaking(program, feed, round);
+      await stake(program, reporter, feed);
+      await startReporting(program, feed, round);
+      record = await report(program, reporter, feed);
+      await startCommitting(program, feed, round);
+      salt = await commitReport(reporter, feed, value, confidence);
+      await startCertifying(program, feed, round);
+    });
+
+    it("rejects a reveal with another confidence than committed", async () => {
+      await expectError(program, reveal(program, reporter, feed, value, confidence + 1, salt), "CommitmentMismatch");
+    });
+
+    it("finalizes a signed value with its confidence and exponent", async () => {
+      await reveal(program, reporter, feed, value, confidence, salt);
+      await certify(program, await fundedUser(program, 1000), feed, record);
+      await startFinalizing(program, feed, round);
+      await finalize(program, feed, [record]);
+      const feedData = await program.account.feed.fetch(feed);
+      assert.equal(feedData.latestFinalizedValue.toNumber(), value);
+      assert.equal(feedData.latestFinalizedConfidence.toNumber(), confidence);
+      assert.equal(feedData.exponent, -8);
+    });
+  });
+
+  describe("aggregation", () => {
+    it("finalizes a trimmed mean", async () => {
+      const feedData = await aggregateWith(1, 2500, [
+        { value: 10, confidence: 0 },
+        { value: 100, confidence: 0 },
+        { value: 102, confidence: 0 },
+        { value: 104, confidence: 0 },
+        { value: 1000, confidence: 0 },
+      ]);
+      assert.equal(feedData.latestFinalizedValue.toNumber(), 102);
+    });
+
+    it("rejects unknown aggregations and trims of half the reports", async () => {
+      await expectError(program, createFeed(program, payer, "AGG/USD", 1000, {aggregation: 9}), "InvalidFeedConfig");
+      const feed = await createFeed(program, payer, "AGG/USD", 1000);
+      await expectError(program, setFeedConfig(program, payer, feed, {aggregation: 1, trimBps: 5000}), "InvalidFeedConfig");
+    });
+  });
+
+  describe("confidence weighting", () => {
+    it("weights reports by the inverse of their confidence", async () => {
+      const feedData = await aggregateWith(3, 0, [
+        { value: 100, confidence: 2 },
+        { value: 200, confidence: 4 },
+        { value: 300, confidence: 4 },
+      ]);
+      assert.equal(feedData.latestFinalizedValue.toNumber(), 175);
+      assert.isAbove(feedData.latestFinalizedConfidence.toNumber(), 0);
+    });
 
-    await startStaking(program, feed, rounds[0]);
-    await stake(program, payer, feed);
+    it("caps the weight of a tight-confidence outlier", async () => {
+      const feedData = await aggregateWith(3, 0, [
+        { value: 100, confidence: 10 },
+        { value: 100, confidence: 10 },
+        { value: 100, confidence: 10 },
+        { value: 100, confidence: 10 },
+        { value: 1000, confidence: 0 },
+      ]);
+      // the outlier carries at most the weight of the four others together
+      assert.equal(feedData.latestFinalizedValue.toNumber(), 550);
+    });
 
+    it("rejects aggregation kinds past the confidence-weighted mean", async () => {
+      await expectError(program, createFeed(program, payer, "AGG/USD", 1000, {aggregation: 4}), "InvalidFeedConfig");
+    });
   });
 });
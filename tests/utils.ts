@@ -1,9 +1,12 @@
 import * as anchor from "@project-serum/anchor";
 import { Program } from "@project-serum/anchor";
 const webcrypto = require('crypto').webcrypto;
+const { createHash } = require('crypto');
 import { 
+  NATIVE_MINT,
   TOKEN_PROGRAM_ID,
   getOrCreateAssociatedTokenAccount,
+  createSyncNativeInstruction,
 } from '@solana/spl-token';
 import {
   Keypair,
@@ -13,9 +16,116 @@ import {
   sendAndConfirmTransaction,
   SystemProgram,
   SYSVAR_RENT_PUBKEY,
+  LAMPORTS_PER_SOL,
 } from "@solana/web3.js";
 
-export async function createFeed(program: Program, payer: Keypair, desc: string, updateInterval: number): PublicKey {
+// Wraps `lamports` of `owner`'s SOL into its wrapped SOL token account.
+export async function wrapSol(program: Program, owner: Keypair, lamports: number): Promise<PublicKey> {
+  const connection = program.provider.connection;
+  const ata = await getOrCreateAssociatedTokenAccount(connection, owner, NATIVE_MINT, owner.publicKey);
+  const tx = new Transaction().add(
+    SystemProgram.transfer({
+      fromPubkey: owner.publicKey,
+      toPubkey: ata.address,
+      lamports: lamports,
+    }),
+    createSyncNativeInstruction(ata.address),
+  );
+  await sendAndConfirmTransaction(connection, tx, [owner]);
+  return ata.address;
+}
+
+// A new keypair with 2 SOL, `wrapped` lamports of which are wrapped.
+export async function fundedUser(program: Program, wrapped: number = 0): Promise<Keypair> {
+  const connection = program.provider.connection;
+  const user = Keypair.generate();
+  const sig = await connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+  await connection.confirmTransaction(sig, "confirmed");
+  if (wrapped > 0) {
+    await wrapSol(program, user, wrapped);
+  }
+  return user;
+}
+
+// Fails unless `promise` rejects with the program or anchor error `name`.
+export async function expectError(program: Program, promise: Promise<any>, name: string) {
+  try {
+    await promise;
+  } catch (err) {
+    const code = program.idl.errors.find((e) => e.name === name)?.code;
+    const text = [
+      String(err),
+      err.error?.errorCode?.code,
+      ...(err.logs ?? []),
+      ...(err.simulationResponse?.logs ?? []),
+    ].join("\n");
+    const matches = text.includes(name) ||
+      (code !== undefined && (text.includes(`"Custom":${code}`) || text.includes(`0x${code.toString(16)}`)));
+    if (!matches) {
+      throw new Error(`expected ${name}, got ${text}`);
+    }
+    return;
+  }
+  throw new Error(`expected ${name}, but it succeeded`);
+}
+
+export async function starboardKey(program: Program): PublicKey {
+  const [starboard] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("Starboard"),
+    ],
+    program.programId
+  );
+  return starboard;
+}
+
+// ProgramData account of the upgradeable loader, which names the
+// upgrade authority allowed to call `init_starboard`
+export async function programDataKey(program: Program): Promise<PublicKey> {
+  const [programData] = await PublicKey.findProgramAddress(
+    [program.programId.toBytes()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+  return programData;
+}
+
+export async function initStarboard(program: Program, admin: Keypair, treasury: PublicKey, protocolFeeBps: number = 0, defaultSlashBandBps: number = 500, defaultSlashMadMultiple: number = 3, defaultStakeMint: PublicKey = new PublicKey("So11111111111111111111111111111111111111112")) {
+  const tx = await program
+      .methods
+      .initStarboard(treasury, protocolFeeBps, defaultStakeMint, defaultSlashBandBps, defaultSlashMadMultiple)
+      .accounts({
+        starboard: await starboardKey(program),
+        admin: admin.publicKey,
+        program: program.programId,
+        programData: await programDataKey(program),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc({skipPreflight: true});
+}
+
+export async function updateStarboard(program: Program, admin: Keypair, update: {admin?: PublicKey, treasury?: PublicKey, protocolFeeBps?: number, defaultStakeMint?: PublicKey, defaultSlashBandBps?: number, defaultSlashMadMultiple?: number}) {
+  const tx = await program
+      .methods
+      .updateStarboard(
+        update.admin ?? null,
+        update.treasury ?? null,
+        update.protocolFeeBps ?? null,
+        update.defaultStakeMint ?? null,
+        update.defaultSlashBandBps ?? null,
+        update.defaultSlashMadMultiple ?? null,
+      )
+      .accounts({
+        starboard: await starboardKey(program),
+        admin: admin.publicKey,
+      })
+      .signers([admin])
+      .rpc({skipPreflight: true});
+}
+
+export async function createFeed(program: Program, payer: Keypair, desc: string, updateInterval: number, params: {exponent?: number, certBond?: number, slashBandBps?: number, slashMadMultiple?: number, rewardPerRound?: number, minReporters?: number, maxReporters?: number, crankBounty?: number | anchor.BN, minStake?: number, maxStake?: number, stakeMint?: PublicKey, nativeStake?: boolean, customStakeMint?: boolean, aggregation?: number, trimBps?: number} = {}): PublicKey {
+  const customStakeMint = params.customStakeMint ?? params.stakeMint !== undefined;
+  const stakeMint = params.stakeMint ?? (await program.account.starboard.fetch(await starboardKey(program))).defaultStakeMint;
   const id = Buffer.alloc(32);
   webcrypto.getRandomValues(id);
   const [feedKey] = await PublicKey.findProgramAddress(
@@ -25,14 +135,58 @@ export async function createFeed(program: Program, payer: Keypair, desc: string,
     ],
     program.programId
   );
+  const [vaultKey] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("FeedVault"),
+      feedKey.toBytes()
+    ],
+    program.programId
+  );
+  const [leaseKey] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("FeedLease"),
+      feedKey.toBytes()
+    ],
+    program.programId
+  );
+  const [programAsSigner] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("program"),
+      Buffer.from("signer"),
+    ],
+    program.programId
+  );
   const tx = await program
       .methods
-      .initFeed(id, Buffer.from(desc.padEnd(32,"\0")), updateInterval)
+      .initFeed(id, {
+        description: Buffer.from(desc.padEnd(32,"\0")),
+        exponent: params.exponent ?? 0,
+        updateInterval: updateInterval,
+        certBond: new anchor.BN(params.certBond ?? 5),
+        slashBandBps: params.slashBandBps ?? null,
+        slashMadMultiple: params.slashMadMultiple ?? null,
+        rewardPerRound: new anchor.BN(params.rewardPerRound ?? 0),
+        minReporters: params.minReporters ?? 1,
+        maxReporters: params.maxReporters ?? 20,
+        crankBounty: new anchor.BN(params.crankBounty ?? 0),
+        minStake: new anchor.BN(params.minStake ?? 5),
+        maxStake: new anchor.BN(params.maxStake ?? 0),
+        nativeStake: params.nativeStake ?? false,
+        customStakeMint: customStakeMint,
+        aggregation: params.aggregation ?? 0,
+        trimBps: params.trimBps ?? 0,
+      })
       .accounts({
+        starboard: await starboardKey(program),
         feed: feedKey,
+        vault: vaultKey,
+        lease: leaseKey,
         payer: payer.publicKey,
+        stakeMint: stakeMint,
+        programAsSigner: programAsSigner,
         systemProgram: SystemProgram.programId,
         rent: SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
       })
       .signers([payer])
       .rpc({skipPreflight: true});
@@ -52,6 +206,7 @@ export async function createRound(program: Program, payer: Keypair, feed: Public
       .methods
       .initRound(num)
       .accounts({
+        starboard: await starboardKey(program),
         round: roundKey,
         feed: feed,
         payer: payer.publicKey,
@@ -68,22 +223,43 @@ export async function startFeed(program: Program, feed: PublicKey) {
       .methods
       .startFeed()
       .accounts({
+        starboard: await starboardKey(program),
         feed: feed,
       })
       .rpc({skipPreflight: true});
 }
 
-export async function startStaking(program: Program, feed: PublicKey, round: PublicKey) {
-  const roundData = await program.account.round.fetch(round);
+export async function keeperAccounts(program: Program, feed: PublicKey) {
+  const provider = program.provider as anchor.AnchorProvider;
   const feedData = await program.account.feed.fetch(feed);
-  const roundNum = roundData.num;
-  const oldRound = feedData.stakingRound;
+  const keeperAta = await getOrCreateAssociatedTokenAccount(provider.connection, (provider.wallet as any).payer, feedData.stakeMint, provider.wallet.publicKey);
+  const [programAsSigner] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("program"),
+      Buffer.from("signer"),
+    ],
+    program.programId
+  );
+  return {
+    keeperTokens: keeperAta.address,
+    lease: feedData.lease,
+    stakeMint: feedData.stakeMint,
+    programAsSigner: programAsSigner,
+    tokenProgram: TOKEN_PROGRAM_ID,
+  };
+}
+
+export async function startStaking(program: Program, feed: PublicKey, round: PublicKey, previousRound: PublicKey = null) {
+  const feedData = await program.account.feed.fetch(feed);
+  const oldRound = previousRound ?? feedData.stakingRound ?? round;
   const tx = await program
     .methods
     .startStaking()
     .accounts({
+      starboard: await starboardKey(program),
       feed: feed,
       round: round,
+      ...(await keeperAccounts(program, feed)),
     })
     .remainingAccounts([{
       isSigner: false,
@@ -93,10 +269,9 @@ export async function startStaking(program: Program, feed: PublicKey, round: Pub
     .rpc({skipPreflight: true});
 }
 
-export async function stake(program: Program, payer: Keypair, feed: PublicKey) {
-
-  const voterAta = await getOrCreateAssociatedTokenAccount(program.provider.connection, payer, new PublicKey("So11111111111111111111111111111111111111112"), payer.publicKey);
+export async function stake(program: Program, payer: Keypair, feed: PublicKey, amount: number = null) {
   const feedData = await program.account.feed.fetch(feed);
+  const voterAta = await getOrCreateAssociatedTokenAccount(program.provider.connection, payer, feedData.stakeMint, payer.publicKey);
   const round = feedData.stakingRound;
   const roundData = await program.account.round.fetch(round);
   const [escrowKey] = await PublicKey.findProgramAddress(
@@ -126,8 +301,9 @@ export async function stake(program: Program, payer: Keypair, feed: PublicKey) {
 
   const tx = await program
     .methods
-    .stake()
+    .stake(amount === null ? feedData.minStake : new anchor.BN(amount))
     .accounts({
+      starboard: await starboardKey(program),
       escrow: escrowKey,
       escrowToken: escrowToken,
       voter: payer.publicKey,
@@ -136,9 +312,971 @@ export async function stake(program: Program, payer: Keypair, feed: PublicKey) {
       round: round,
       systemProgram: SystemProgram.programId,
       rent: SYSVAR_RENT_PUBKEY,
-      nativeMint: new PublicKey("So11111111111111111111111111111111111111112"),
+      stakeMint: feedData.stakeMint,
+      programAsSigner: programAsSigner,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+}
+
+export async function startReporting(program: Program, feed: PublicKey, round: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const oldRound = feedData.reportingRound ?? round;
+  const tx = await program
+    .methods
+    .startReporting()
+    .accounts({
+      starboard: await starboardKey(program),
+      feed: feed,
+      round: round,
+      ...(await keeperAccounts(program, feed)),
+    })
+    .remainingAccounts([{
+      isSigner: false,
+      isMutable: false,
+      pubkey: oldRound,
+    }])
+    .rpc({skipPreflight: true});
+}
+
+export async function report(program: Program, payer: Keypair, feed: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const reporterAta = await getOrCreateAssociatedTokenAccount(program.provider.connection, payer, feedData.stakeMint, payer.publicKey);
+  const round = feedData.reportingRound;
+  const [escrowKey] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("Escrow"),
+      payer.publicKey.toBytes(),
+      round.toBytes(),
+    ],
+    program.programId
+  );
+
+  const [reportRecord] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("ReportRecordSeed"),
+      round.toBytes(),
+      payer.publicKey.toBytes(),
+    ],
+    program.programId
+  );
+
+  const [reportEscrow] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("EscrowToken"),
+      reportRecord.toBytes()
+    ],
+    program.programId
+  );
+
+  const [programAsSigner] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("program"),
+      Buffer.from("signer"),
+    ],
+    program.programId
+  );
+
+  const tx = await program
+    .methods
+    .report()
+    .accounts({
+      starboard: await starboardKey(program),
+      feed: feed,
+      round: round,
+      reporter: payer.publicKey,
+      escrow: escrowKey,
+      reporterToken: reporterAta.address,
+      reportRecord: reportRecord,
+      reportEscrow: reportEscrow,
+      stakeMint: feedData.stakeMint,
+      programAsSigner: programAsSigner,
+      systemProgram: SystemProgram.programId,
+      rent: SYSVAR_RENT_PUBKEY,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+  return reportRecord;
+}
+
+export function commitmentHash(feed: PublicKey, roundHeight: number, staker: PublicKey, value: number, confidence: number, salt: Buffer): Buffer {
+  return createHash("sha256")
+    .update(Buffer.from("StarboardCommitment"))
+    .update(feed.toBuffer())
+    .update(new anchor.BN(roundHeight).toArrayLike(Buffer, "le", 8))
+    .update(staker.toBuffer())
+    .update(new anchor.BN(value).toTwos(64).toArrayLike(Buffer, "le", 8))
+    .update(new anchor.BN(confidence).toArrayLike(Buffer, "le", 8))
+    .update(salt)
+    .digest();
+}
+
+export async function startCommitting(program: Program, feed: PublicKey, round: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const oldRound = feedData.committingRound ?? round;
+  const tx = await program
+    .methods
+    .startCommitting()
+    .accounts({
+      starboard: await starboardKey(program),
+      feed: feed,
+      round: round,
+      ...(await keeperAccounts(program, feed)),
+    })
+    .remainingAccounts([{
+      isSigner: false,
+      isMutable: false,
+      pubkey: oldRound,
+    }])
+    .rpc({skipPreflight: true});
+}
+
+export async function committ(program: Program, payer: Keypair, feed: PublicKey, commitment: Buffer) {
+  const feedData = await program.account.feed.fetch(feed);
+  const round = feedData.committingRound;
+  const [escrowKey] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("Escrow"),
+      payer.publicKey.toBytes(),
+      round.toBytes(),
+    ],
+    program.programId
+  );
+  const tx = await program
+    .methods
+    .committ([...commitment])
+    .accounts({
+      starboard: await starboardKey(program),
+      feed: feed,
+      round: round,
+      escrow: escrowKey,
+      staker: payer.publicKey,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+}
+
+export async function startCertifying(program: Program, feed: PublicKey, round: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const oldRound = feedData.certifyingRound ?? round;
+  const tx = await program
+    .methods
+    .startCertifying()
+    .accounts({
+      starboard: await starboardKey(program),
+      feed: feed,
+      round: round,
+      ...(await keeperAccounts(program, feed)),
+    })
+    .remainingAccounts([{
+      isSigner: false,
+      isMutable: false,
+      pubkey: oldRound,
+    }])
+    .rpc({skipPreflight: true});
+}
+
+export async function reveal(program: Program, payer: Keypair, feed: PublicKey, value: number, confidence: number, salt: Buffer) {
+  const feedData = await program.account.feed.fetch(feed);
+  const round = feedData.certifyingRound;
+  const [escrowKey] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("Escrow"),
+      payer.publicKey.toBytes(),
+      round.toBytes(),
+    ],
+    program.programId
+  );
+  const [reportRecord] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("ReportRecordSeed"),
+      round.toBytes(),
+      payer.publicKey.toBytes(),
+    ],
+    program.programId
+  );
+  const tx = await program
+    .methods
+    .reveal(new anchor.BN(value), new anchor.BN(confidence), [...salt])
+    .accounts({
+      starboard: await starboardKey(program),
+      feed: feed,
+      round: round,
+      escrow: escrowKey,
+      reportRecord: reportRecord,
+      staker: payer.publicKey,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+}
+
+export async function certify(program: Program, payer: Keypair, feed: PublicKey, reportRecord: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const certifierAta = await getOrCreateAssociatedTokenAccount(program.provider.connection, payer, feedData.stakeMint, payer.publicKey);
+  const round = feedData.certifyingRound;
+  const [certRecord] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("CertRecordSeed"),
+      reportRecord.toBytes(),
+      payer.publicKey.toBytes(),
+    ],
+    program.programId
+  );
+
+  const [certifierEscrow] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("EscrowToken"),
+      certRecord.toBytes()
+    ],
+    program.programId
+  );
+
+  const [programAsSigner] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("program"),
+      Buffer.from("signer"),
+    ],
+    program.programId
+  );
+
+  const tx = await program
+    .methods
+    .certify()
+    .accounts({
+      starboard: await starboardKey(program),
+      feed: feed,
+      round: round,
+      reportRecord: reportRecord,
+      certifier: payer.publicKey,
+      certifierTokens: certifierAta.address,
+      certifierEscrow: certifierEscrow,
+      certRecord: certRecord,
       programAsSigner: programAsSigner,
+      systemProgram: SystemProgram.programId,
+      rent: SYSVAR_RENT_PUBKEY,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      stakeMint: feedData.stakeMint,
     })
     .signers([payer])
     .rpc({skipPreflight: true});
+  return certRecord;
+}
+
+export async function startFinalizing(program: Program, feed: PublicKey, round: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const oldRound = feedData.finalizingRound ?? round;
+  const tx = await program
+    .methods
+    .startFinalizing()
+    .accounts({
+      starboard: await starboardKey(program),
+      feed: feed,
+      round: round,
+      ...(await keeperAccounts(program, feed)),
+    })
+    .remainingAccounts([{
+      isSigner: false,
+      isMutable: false,
+      pubkey: oldRound,
+    }])
+    .rpc({skipPreflight: true});
+}
+
+export async function finalize(program: Program, feed: PublicKey, reportRecords: PublicKey[]) {
+  const feedData = await program.account.feed.fetch(feed);
+  const round = feedData.finalizingRound;
+  const [programAsSigner] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("program"),
+      Buffer.from("signer"),
+    ],
+    program.programId
+  );
+  // the treasury's token account for the feed's stake mint
+  const provider = program.provider as anchor.AnchorProvider;
+  const treasuryOwner = (await program.account.starboard.fetch(await starboardKey(program))).treasury;
+  const treasury = await getOrCreateAssociatedTokenAccount(provider.connection, (provider.wallet as any).payer, feedData.stakeMint, treasuryOwner);
+  const tx = await program
+    .methods
+    .finalize()
+    .accounts({
+      starboard: await starboardKey(program),
+      feed: feed,
+      round: round,
+      vault: feedData.vault,
+      lease: feedData.lease,
+      treasury: treasury.address,
+      programAsSigner: programAsSigner,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .remainingAccounts(reportRecords.map((pubkey) => ({
+      isSigner: false,
+      isWritable: true,
+      pubkey: pubkey,
+    })))
+    .rpc({skipPreflight: true});
+}
+
+export async function slashReporter(program: Program, feed: PublicKey, reportRecord: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const recordData = await program.account.reportRecord.fetch(reportRecord);
+  const [escrowToken] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("EscrowToken"),
+      recordData.escrow.toBytes()
+    ],
+    program.programId
+  );
+  const [programAsSigner] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("program"),
+      Buffer.from("signer"),
+    ],
+    program.programId
+  );
+  const tx = await program
+    .methods
+    .slashReporter()
+    .accounts({
+      starboard: await starboardKey(program),
+      feed: feed,
+      reportRecord: reportRecord,
+      escrow: recordData.escrow,
+      escrowToken: escrowToken,
+      vault: feedData.vault,
+      staker: recordData.reporter,
+      programAsSigner: programAsSigner,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .rpc({skipPreflight: true});
+}
+
+export async function slashCertifier(program: Program, feed: PublicKey, certRecord: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const certData = await program.account.certRecord.fetch(certRecord);
+  const [programAsSigner] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("program"),
+      Buffer.from("signer"),
+    ],
+    program.programId
+  );
+  const tx = await program
+    .methods
+    .slashCertifier()
+    .accounts({
+      starboard: await starboardKey(program),
+      feed: feed,
+      reportRecord: certData.reportRecord,
+      certRecord: certRecord,
+      certifierEscrow: certData.escrow,
+      vault: feedData.vault,
+      certifier: certData.certifier,
+      programAsSigner: programAsSigner,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .rpc({skipPreflight: true});
+}
+
+export async function claimRewards(program: Program, payer: Keypair, feed: PublicKey, reportRecord: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const reporterAta = await getOrCreateAssociatedTokenAccount(program.provider.connection, payer, feedData.stakeMint, payer.publicKey);
+  const [programAsSigner] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("program"),
+      Buffer.from("signer"),
+    ],
+    program.programId
+  );
+  const tx = await program
+    .methods
+    .claimRewards()
+    .accounts({
+      feed: feed,
+      reportRecord: reportRecord,
+      reporter: payer.publicKey,
+      reporterTokens: reporterAta.address,
+      vault: feedData.vault,
+      stakeMint: feedData.stakeMint,
+      programAsSigner: programAsSigner,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+}
+
+export async function claimCertRewards(program: Program, payer: Keypair, feed: PublicKey, certRecord: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const certifierAta = await getOrCreateAssociatedTokenAccount(program.provider.connection, payer, feedData.stakeMint, payer.publicKey);
+  const certData = await program.account.certRecord.fetch(certRecord);
+  const [programAsSigner] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("program"),
+      Buffer.from("signer"),
+    ],
+    program.programId
+  );
+  const tx = await program
+    .methods
+    .claimCertRewards()
+    .accounts({
+      feed: feed,
+      round: certData.round,
+      reportRecord: certData.reportRecord,
+      certRecord: certRecord,
+      certifierEscrow: certData.escrow,
+      certifier: payer.publicKey,
+      certifierTokens: certifierAta.address,
+      vault: feedData.vault,
+      stakeMint: feedData.stakeMint,
+      programAsSigner: programAsSigner,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+}
+
+export async function withdrawStake(program: Program, payer: Keypair, feed: PublicKey, round: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const stakerAta = await getOrCreateAssociatedTokenAccount(program.provider.connection, payer, feedData.stakeMint, payer.publicKey);
+  const [escrowKey] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("Escrow"),
+      payer.publicKey.toBytes(),
+      round.toBytes(),
+    ],
+    program.programId
+  );
+  const [escrowToken] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("EscrowToken"),
+      escrowKey.toBytes()
+    ],
+    program.programId
+  );
+  const [reportRecord] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("ReportRecordSeed"),
+      round.toBytes(),
+      payer.publicKey.toBytes(),
+    ],
+    program.programId
+  );
+  const [programAsSigner] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("program"),
+      Buffer.from("signer"),
+    ],
+    program.programId
+  );
+  const tx = await program
+    .methods
+    .withdrawStake()
+    .accounts({
+      feed: feed,
+      round: round,
+      escrow: escrowKey,
+      escrowToken: escrowToken,
+      reportRecord: reportRecord,
+      staker: payer.publicKey,
+      stakerTokens: stakerAta.address,
+      stakeMint: feedData.stakeMint,
+      programAsSigner: programAsSigner,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+}
+
+export async function failRound(program: Program, feed: PublicKey, round: PublicKey) {
+  const tx = await program
+    .methods
+    .failRound()
+    .accounts({
+      starboard: await starboardKey(program),
+      feed: feed,
+      round: round,
+    })
+    .rpc({skipPreflight: true});
+}
+
+export async function closeReport(program: Program, payer: Keypair, feed: PublicKey, reportRecord: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const reporterAta = await getOrCreateAssociatedTokenAccount(program.provider.connection, payer, feedData.stakeMint, payer.publicKey);
+  const recordData = await program.account.reportRecord.fetch(reportRecord);
+  const [reportEscrow] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("EscrowToken"),
+      reportRecord.toBytes()
+    ],
+    program.programId
+  );
+  const [programAsSigner] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("program"),
+      Buffer.from("signer"),
+    ],
+    program.programId
+  );
+  const tx = await program
+    .methods
+    .closeReport()
+    .accounts({
+      feed: feed,
+      round: recordData.round,
+      reportRecord: reportRecord,
+      reportEscrow: reportEscrow,
+      escrow: recordData.escrow,
+      reporter: payer.publicKey,
+      reporterTokens: reporterAta.address,
+      stakeMint: feedData.stakeMint,
+      programAsSigner: programAsSigner,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+}
+
+export async function crank(program: Program, feed: PublicKey) {
+  const rounds = [];
+  for (let num = 0; num < 5; num++) {
+    const [roundKey] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from("Round"),
+        feed.toBytes(),
+        Buffer.from([num]),
+      ],
+      program.programId
+    );
+    rounds.push(roundKey);
+  }
+  const tx = await program
+    .methods
+    .crank()
+    .accounts({
+      starboard: await starboardKey(program),
+      feed: feed,
+      round0: rounds[0],
+      round1: rounds[1],
+      round2: rounds[2],
+      round3: rounds[3],
+      round4: rounds[4],
+      ...(await keeperAccounts(program, feed)),
+    })
+    .rpc({skipPreflight: true});
+}
+
+export async function fundLease(program: Program, payer: Keypair, feed: PublicKey, amount: number) {
+  const feedData = await program.account.feed.fetch(feed);
+  const funderAta = await getOrCreateAssociatedTokenAccount(program.provider.connection, payer, feedData.stakeMint, payer.publicKey);
+  const tx = await program
+    .methods
+    .fundLease(new anchor.BN(amount))
+    .accounts({
+      starboard: await starboardKey(program),
+      feed: feed,
+      lease: feedData.lease,
+      funder: payer.publicKey,
+      funderTokens: funderAta.address,
+      stakeMint: feedData.stakeMint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+}
+
+export async function withdrawLease(program: Program, authority: Keypair, feed: PublicKey, amount: number) {
+  const feedData = await program.account.feed.fetch(feed);
+  const recipientAta = await getOrCreateAssociatedTokenAccount(program.provider.connection, authority, feedData.stakeMint, authority.publicKey);
+  const [programAsSigner] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("program"),
+      Buffer.from("signer"),
+    ],
+    program.programId
+  );
+  const tx = await program
+    .methods
+    .withdrawLease(new anchor.BN(amount))
+    .accounts({
+      feed: feed,
+      lease: feedData.lease,
+      leaseAuthority: authority.publicKey,
+      recipientTokens: recipientAta.address,
+      stakeMint: feedData.stakeMint,
+      programAsSigner: programAsSigner,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([authority])
+    .rpc({skipPreflight: true});
+}
+
+export async function setFeedConfig(program: Program, authority: Keypair, feed: PublicKey, config: {description?: string, updateInterval?: number, minStake?: number, maxStake?: number, minReporters?: number, maxReporters?: number, slashBandBps?: number, slashMadMultiple?: number, aggregation?: number, trimBps?: number}) {
+  const tx = await program
+    .methods
+    .setFeedConfig({
+      description: config.description === undefined ? null : Buffer.from(config.description.padEnd(32,"\0")),
+      updateInterval: config.updateInterval ?? null,
+      minStake: config.minStake === undefined ? null : new anchor.BN(config.minStake),
+      maxStake: config.maxStake === undefined ? null : new anchor.BN(config.maxStake),
+      minReporters: config.minReporters ?? null,
+      maxReporters: config.maxReporters ?? null,
+      slashBandBps: config.slashBandBps ?? null,
+      slashMadMultiple: config.slashMadMultiple ?? null,
+      aggregation: config.aggregation ?? null,
+      trimBps: config.trimBps ?? null,
+    })
+    .accounts({
+      feed: feed,
+      authority: authority.publicKey,
+    })
+    .signers([authority])
+    .rpc({skipPreflight: true});
+}
+
+export async function transferFeedAuthority(program: Program, authority: Keypair, feed: PublicKey, newAuthority: PublicKey) {
+  const tx = await program
+    .methods
+    .transferFeedAuthority(newAuthority)
+    .accounts({
+      feed: feed,
+      authority: authority.publicKey,
+    })
+    .signers([authority])
+    .rpc({skipPreflight: true});
+}
+
+export async function setProtocolPaused(program: Program, admin: Keypair, paused: boolean) {
+  const tx = await (paused ? program.methods.pauseProtocol() : program.methods.unpauseProtocol())
+    .accounts({
+      starboard: await starboardKey(program),
+      admin: admin.publicKey,
+    })
+    .signers([admin])
+    .rpc({skipPreflight: true});
+}
+
+export async function setFeedPaused(program: Program, signer: Keypair, feed: PublicKey, paused: boolean) {
+  const tx = await (paused ? program.methods.pauseFeed() : program.methods.unpauseFeed())
+    .accounts({
+      feed: feed,
+      starboard: await starboardKey(program),
+      signer: signer.publicKey,
+    })
+    .signers([signer])
+    .rpc({skipPreflight: true});
+}
+
+export async function stakeNative(program: Program, payer: Keypair, feed: PublicKey, amount: number = null) {
+  const feedData = await program.account.feed.fetch(feed);
+  const round = feedData.stakingRound;
+  const [escrowKey] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("Escrow"),
+      payer.publicKey.toBytes(),
+      round.toBytes(),
+    ],
+    program.programId
+  );
+  const tx = await program
+    .methods
+    .stakeNative(amount === null ? feedData.minStake : new anchor.BN(amount))
+    .accounts({
+      escrow: escrowKey,
+      voter: payer.publicKey,
+      feed: feed,
+      starboard: await starboardKey(program),
+      round: round,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+}
+
+export async function reportNative(program: Program, payer: Keypair, feed: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const round = feedData.reportingRound;
+  const [escrowKey] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("Escrow"),
+      payer.publicKey.toBytes(),
+      round.toBytes(),
+    ],
+    program.programId
+  );
+  const [reportRecord] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("ReportRecordSeed"),
+      round.toBytes(),
+      payer.publicKey.toBytes(),
+    ],
+    program.programId
+  );
+  const tx = await program
+    .methods
+    .reportNative()
+    .accounts({
+      starboard: await starboardKey(program),
+      feed: feed,
+      round: round,
+      reporter: payer.publicKey,
+      escrow: escrowKey,
+      reportRecord: reportRecord,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+  return reportRecord;
+}
+
+export async function certifyNative(program: Program, payer: Keypair, feed: PublicKey, reportRecord: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const round = feedData.certifyingRound;
+  const [certRecord] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("CertRecordSeed"),
+      reportRecord.toBytes(),
+      payer.publicKey.toBytes(),
+    ],
+    program.programId
+  );
+  const tx = await program
+    .methods
+    .certifyNative()
+    .accounts({
+      feed: feed,
+      starboard: await starboardKey(program),
+      round: round,
+      reportRecord: reportRecord,
+      certifier: payer.publicKey,
+      certRecord: certRecord,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+  return certRecord;
+}
+
+export async function withdrawStakeNative(program: Program, payer: Keypair, feed: PublicKey, round: PublicKey) {
+  const [escrowKey] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("Escrow"),
+      payer.publicKey.toBytes(),
+      round.toBytes(),
+    ],
+    program.programId
+  );
+  const [reportRecord] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("ReportRecordSeed"),
+      round.toBytes(),
+      payer.publicKey.toBytes(),
+    ],
+    program.programId
+  );
+  const tx = await program
+    .methods
+    .withdrawStakeNative()
+    .accounts({
+      feed: feed,
+      round: round,
+      escrow: escrowKey,
+      reportRecord: reportRecord,
+      staker: payer.publicKey,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+}
+
+export async function claimRewardsNative(program: Program, payer: Keypair, feed: PublicKey, reportRecord: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const [payout] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("Payout"),
+      reportRecord.toBytes()
+    ],
+    program.programId
+  );
+  const [programAsSigner] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("program"),
+      Buffer.from("signer"),
+    ],
+    program.programId
+  );
+  const tx = await program
+    .methods
+    .claimRewardsNative()
+    .accounts({
+      feed: feed,
+      reportRecord: reportRecord,
+      reporter: payer.publicKey,
+      payout: payout,
+      vault: feedData.vault,
+      stakeMint: feedData.stakeMint,
+      programAsSigner: programAsSigner,
+      systemProgram: SystemProgram.programId,
+      rent: SYSVAR_RENT_PUBKEY,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+}
+
+export async function claimCertRewardsNative(program: Program, payer: Keypair, feed: PublicKey, certRecord: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const certData = await program.account.certRecord.fetch(certRecord);
+  const [payout] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("Payout"),
+      certRecord.toBytes()
+    ],
+    program.programId
+  );
+  const [programAsSigner] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("program"),
+      Buffer.from("signer"),
+    ],
+    program.programId
+  );
+  const tx = await program
+    .methods
+    .claimCertRewardsNative()
+    .accounts({
+      feed: feed,
+      round: certData.round,
+      reportRecord: certData.reportRecord,
+      certRecord: certRecord,
+      certifier: payer.publicKey,
+      payout: payout,
+      vault: feedData.vault,
+      stakeMint: feedData.stakeMint,
+      programAsSigner: programAsSigner,
+      systemProgram: SystemProgram.programId,
+      rent: SYSVAR_RENT_PUBKEY,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+}
+
+export async function closeReportNative(program: Program, payer: Keypair, feed: PublicKey, reportRecord: PublicKey) {
+  const recordData = await program.account.reportRecord.fetch(reportRecord);
+  const tx = await program
+    .methods
+    .closeReportNative()
+    .accounts({
+      feed: feed,
+      round: recordData.round,
+      reportRecord: reportRecord,
+      escrow: recordData.escrow,
+      reporter: payer.publicKey,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+}
+
+export async function slashReporterNative(program: Program, feed: PublicKey, reportRecord: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const recordData = await program.account.reportRecord.fetch(reportRecord);
+  const tx = await program
+    .methods
+    .slashReporterNative()
+    .accounts({
+      feed: feed,
+      starboard: await starboardKey(program),
+      reportRecord: reportRecord,
+      escrow: recordData.escrow,
+      vault: feedData.vault,
+      staker: recordData.reporter,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .rpc({skipPreflight: true});
+}
+
+export async function slashCertifierNative(program: Program, feed: PublicKey, certRecord: PublicKey) {
+  const feedData = await program.account.feed.fetch(feed);
+  const certData = await program.account.certRecord.fetch(certRecord);
+  const tx = await program
+    .methods
+    .slashCertifierNative()
+    .accounts({
+      feed: feed,
+      starboard: await starboardKey(program),
+      reportRecord: certData.reportRecord,
+      certRecord: certRecord,
+      vault: feedData.vault,
+      certifier: certData.certifier,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .rpc({skipPreflight: true});
+}
+
+export async function topUpStake(program: Program, payer: Keypair, feed: PublicKey, amount: number) {
+  const feedData = await program.account.feed.fetch(feed);
+  const voterAta = await getOrCreateAssociatedTokenAccount(program.provider.connection, payer, feedData.stakeMint, payer.publicKey);
+  const round = feedData.stakingRound;
+  const [escrowKey] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("Escrow"),
+      payer.publicKey.toBytes(),
+      round.toBytes(),
+    ],
+    program.programId
+  );
+  const escrowData = await program.account.escrow.fetch(escrowKey);
+  const tx = await program
+    .methods
+    .topUpStake(new anchor.BN(amount))
+    .accounts({
+      escrow: escrowKey,
+      escrowToken: escrowData.tokenAccount,
+      voter: payer.publicKey,
+      voterTokenAccount: voterAta.address,
+      feed: feed,
+      starboard: await starboardKey(program),
+      round: round,
+      stakeMint: feedData.stakeMint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+}
+
+export async function topUpStakeNative(program: Program, payer: Keypair, feed: PublicKey, amount: number) {
+  const feedData = await program.account.feed.fetch(feed);
+  const round = feedData.stakingRound;
+  const [escrowKey] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from("Escrow"),
+      payer.publicKey.toBytes(),
+      round.toBytes(),
+    ],
+    program.programId
+  );
+  const tx = await program
+    .methods
+    .topUpStakeNative(new anchor.BN(amount))
+    .accounts({
+      escrow: escrowKey,
+      voter: payer.publicKey,
+      feed: feed,
+      starboard: await starboardKey(program),
+      round: round,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([payer])
+    .rpc({skipPreflight: true});
+}
+
+export async function readFeed(program: Program, feed: PublicKey, maxAgeSlots: number) {
+  return await program
+    .methods
+    .readFeed(new anchor.BN(maxAgeSlots))
+    .accounts({
+      feed: feed,
+      starboard: await starboardKey(program),
+    })
+    .view();
 }